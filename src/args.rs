@@ -1,15 +1,300 @@
-use clap::Parser;
+use std::fs;
+use std::time::Duration;
 
+use clap::{Parser, Subcommand};
+
+use automatonator::shared::automaton::ParseError;
+use automatonator::shared::batch::IdSource;
+use automatonator::shared::budget::Budget;
+use automatonator::shared::evaluation::{GenerationConfig, LengthDistribution, Relation};
+use automatonator::shared::feedback::ReportFormat;
+use automatonator::shared::operations::Operation;
+use automatonator::shared::utils::parse_alphabet;
+
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about,
+    after_help = "Without a command, the arguments are read in the positional form \
+                  `automatonator <AUTOMATON> [AUTOMATON2] [OPTIONS]` (see `automatonator <AUTOMATON> --help`)"
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Show an automaton and optionally report properties of its language
+    View(ViewArgs),
+    /// Test an automaton against a file with words
+    Check(CheckArgs),
+    /// Compare two automatons on generated words
+    Compare(CompareArgs),
+    /// Grade an automaton against a reference with an evaluation file
+    Grade(GradeArgs),
+    /// Grade an automaton against a reference with a rubric of weighted criteria
+    Rubric(RubricArgs),
+    /// Grade many submissions against a reference and export the grades
+    Batch(BatchArgs),
+    /// Write automatons in the text format, optionally building a new one with an operation
+    Convert(ConvertArgs),
+    /// Write the minimal DFA of a finite automaton in the text format
+    Minimize(MinimizeArgs),
+    /// Report unreachable and dead states and other mistakes in the structure of an automaton
+    Lint(AutomatonArgs),
+    /// Show the states an automaton visits while reading a word
+    Trace(TraceArgs),
+}
+
+impl Cli {
+    // The subcommands, or the positional form if the first argument is no command
+    // A file named like a command has to be given as a path in the positional form (./check)
+    pub fn parse_any() -> Result<Cli, Box<LegacyArgs>> {
+        let first = std::env::args().nth(1).unwrap_or_default();
+        let commands = [
+            "view", "check", "compare", "grade", "rubric", "batch", "convert", "minimize", "lint",
+            "trace", "help",
+        ];
+        if commands.contains(&first.as_str())
+            || ["-h", "--help", "-V", "--version"].contains(&first.as_str())
+        {
+            Ok(Cli::parse())
+        } else {
+            Err(Box::new(LegacyArgs::parse()))
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub struct AutomatonArgs {
+    /// The Automaton
+    pub automaton: String,
+
+    /// The Automaton Type (dfa, nfa, pda, mealy, moore, buchi, 2dfa, lba, counter, wfa), if ommited, will be inferred by filename
+    #[arg(short = 't', long = "type")]
+    pub automaton_type: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct PairArgs {
+    /// The Automaton to test
+    pub automaton: String,
+
+    /// The Reference Automaton to test against
+    pub automaton2: String,
+
+    /// The Automaton Type (dfa, nfa, pda, mealy, moore, buchi, 2dfa, lba, counter, wfa), if ommited, will be inferred by filename
+    #[arg(short = 't', long = "type")]
+    pub automaton_type: Option<String>,
+
+    /// The Automaton Type of the Reference Automaton (in case it differs from main type)
+    #[arg(short = 'r', long = "reftype")]
+    pub ref_automaton_type: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ViewArgs {
+    #[command(flatten)]
+    pub automaton: AutomatonArgs,
+
+    /// Report properties of the language
+    /// (emptiness, shortest word and for finite automatons finiteness, universality and word counts)
+    #[arg(long = "analyze")]
+    pub analyze: bool,
+
+    /// Count the accepted words up to this length when analyzing
+    #[arg(long = "count-length", default_value_t = 10)]
+    pub count_length: usize,
+
+    #[command(flatten)]
+    pub alphabet: AlphabetArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CheckArgs {
+    #[command(flatten)]
+    pub automaton: AutomatonArgs,
+
+    /// Path to a File with words to check (line format: "word", "+ word" or "- word")
+    pub cases: String,
+
+    #[command(flatten)]
+    pub limits: LimitArgs,
+
+    #[command(flatten)]
+    pub feedback: FeedbackArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CompareArgs {
+    #[command(flatten)]
+    pub automatons: PairArgs,
+
+    #[command(flatten)]
+    pub limits: LimitArgs,
+
+    #[command(flatten)]
+    pub feedback: FeedbackArgs,
+
+    #[command(flatten)]
+    pub generation: GenerationArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct GradeArgs {
+    #[command(flatten)]
+    pub automatons: PairArgs,
+
+    /// Path to a File with words to check for evaluation (line format: "word points")
+    pub evaluation_file: String,
+
+    #[command(flatten)]
+    pub limits: LimitArgs,
+
+    #[command(flatten)]
+    pub feedback: FeedbackArgs,
+
+    #[command(flatten)]
+    pub generation: GenerationArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RubricArgs {
+    #[command(flatten)]
+    pub automatons: PairArgs,
+
+    /// Path to the rubric (line format: "criterion points arguments")
+    pub rubric: String,
+
+    #[command(flatten)]
+    pub limits: LimitArgs,
+
+    #[command(flatten)]
+    pub feedback: FeedbackArgs,
+
+    #[command(flatten)]
+    pub generation: GenerationArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct BatchArgs {
+    /// The Reference Automaton
+    pub reference: String,
+
+    /// The submitted Automatons
+    #[arg(required = true)]
+    pub submissions: Vec<String>,
+
+    /// The Automaton Type of the submissions, if ommited, will be inferred by filename
+    #[arg(short = 't', long = "type")]
+    pub automaton_type: Option<String>,
+
+    /// The Automaton Type of the Reference Automaton (in case it differs from the submissions)
+    #[arg(short = 'r', long = "reftype")]
+    pub ref_automaton_type: Option<String>,
+
+    /// Path to a File with words to check for evaluation, the generated comparison is used if omitted
+    #[arg(short = 'e', long = "evaluation")]
+    pub evaluation_file: Option<String>,
+
+    /// Where the id of a student is taken from
+    #[arg(long = "id-from", value_enum, default_value_t = IdSource::File)]
+    pub id_source: IdSource,
+
+    /// Write id, file, score, verdict and error of every submission to this CSV file
+    /// ("-" or neither --csv nor --moodle prints it)
+    #[arg(long = "csv")]
+    pub csv: Option<String>,
+
+    /// Write a grade import CSV for Moodle to this file
+    #[arg(long = "moodle")]
+    pub moodle: Option<String>,
+
+    /// Name of the column with the student ids in the Moodle CSV
+    #[arg(long = "moodle-id-column", default_value = "ID number")]
+    pub moodle_id_column: String,
+
+    /// Name of the column with the grades in the Moodle CSV
+    #[arg(long = "moodle-grade-column", default_value = "Grade")]
+    pub moodle_grade_column: String,
+
+    /// The grade for a full score in the Moodle CSV
+    #[arg(long = "moodle-max-grade", default_value_t = 100.0)]
+    pub moodle_max_grade: f64,
+
+    #[command(flatten)]
+    pub limits: LimitArgs,
+
+    #[command(flatten)]
+    pub generation: GenerationArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ConvertArgs {
+    /// The Automaton to convert
+    pub automaton: String,
+
+    /// The second Automaton for operations on two automatons
+    pub automaton2: Option<String>,
+
+    /// The Automaton Type (dfa, nfa, pda, mealy, moore, buchi, 2dfa, lba, counter, wfa), if ommited, will be inferred by filename
+    #[arg(short = 't', long = "type")]
+    pub automaton_type: Option<String>,
+
+    /// The Automaton Type of the second Automaton (in case it differs from main type)
+    #[arg(short = 'r', long = "reftype")]
+    pub ref_automaton_type: Option<String>,
+
+    /// Build a new automaton from the given one(s)
+    /// (union, intersection and concatenation use both automatons)
+    #[arg(long = "operation", value_enum)]
+    pub operation: Option<Operation>,
+
+    /// Path to write the automaton to in the text format (printed if omitted)
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub alphabet: AlphabetArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct MinimizeArgs {
+    #[command(flatten)]
+    pub automaton: AutomatonArgs,
+
+    /// Path to write the minimal DFA to in the text format (printed if omitted)
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub alphabet: AlphabetArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TraceArgs {
+    #[command(flatten)]
+    pub automaton: AutomatonArgs,
+
+    /// The word to read (ε for the empty word)
+    pub word: String,
+
+    #[command(flatten)]
+    pub limits: LimitArgs,
+}
+
+// The positional form, kept so existing scripts keep working
 #[derive(Parser, Debug)]
 #[command(version, about)]
-pub struct Args {
+pub struct LegacyArgs {
     /// The Automaton to test
     pub automaton: String,
 
     /// The Reference Automaton to test against
     pub automaton2: Option<String>,
 
-    /// The Automaton Type (dfa, nfa, pda, tm), if ommited, will be inferred by filename
+    /// The Automaton Type (dfa, nfa, pda, mealy, moore, buchi, 2dfa, lba, counter, wfa), if ommited, will be inferred by filename
     #[arg(short = 't', long = "type")]
     pub automaton_type: Option<String>,
 
@@ -24,4 +309,260 @@ pub struct Args {
     /// Path to a File with words to check for evaluation (line format: "word percentage")
     #[arg(short = 'e', long = "eval")]
     pub evaluation_file: Option<String>,
+
+    /// Report properties of the language of the first automaton
+    /// (emptiness, shortest word and for finite automatons finiteness, universality and word counts)
+    #[arg(long = "analyze")]
+    pub analyze: bool,
+
+    /// Count the accepted words up to this length when analyzing
+    #[arg(long = "count-length", default_value_t = 10)]
+    pub count_length: usize,
+
+    /// Build a new automaton from the given one(s) instead of testing them
+    /// (union, intersection and concatenation use both automatons)
+    #[arg(long = "operation", value_enum)]
+    pub operation: Option<Operation>,
+
+    /// Path to write the automaton built with --operation to in the text format (printed if omitted)
+    #[arg(short = 'o', long = "output", requires = "operation")]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub limits: LimitArgs,
+
+    #[command(flatten)]
+    pub feedback: FeedbackArgs,
+
+    #[command(flatten)]
+    pub generation: GenerationArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct FeedbackArgs {
+    /// Write a report for the student to this file ("-" writes it to the terminal)
+    #[arg(long = "feedback")]
+    pub feedback: Option<String>,
+
+    /// The format of the feedback report
+    #[arg(long = "feedback-format", value_enum, default_value_t = ReportFormat::Markdown)]
+    pub feedback_format: ReportFormat,
+
+    /// Write the results of every test word, evaluation line and comparison as JUnit XML to this file
+    #[arg(long = "junit")]
+    pub junit: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct LimitArgs {
+    /// Maximum time in seconds to simulate a single word
+    #[arg(long = "word-timeout", default_value_t = 1.0, value_parser = seconds)]
+    pub word_timeout: f64,
+
+    /// Maximum time in seconds for all simulations of a run (unlimited if omitted)
+    #[arg(long = "total-timeout", value_parser = seconds)]
+    pub total_timeout: Option<f64>,
+
+    /// Maximum number of configurations visited while simulating a single word
+    #[arg(long = "max-configurations", default_value_t = 1_000_000)]
+    pub max_configurations: usize,
+}
+
+// A duration in seconds, checked here so clap reports it instead of Duration panicking
+fn seconds(input: &str) -> Result<f64, String> {
+    let seconds: f64 = input.parse().map_err(|error| format!("{}", error))?;
+    if seconds.is_finite() && seconds >= 0.0 {
+        Ok(seconds)
+    } else {
+        Err("expected a non-negative number of seconds".to_string())
+    }
+}
+
+impl LimitArgs {
+    pub fn budget(&self) -> Budget {
+        Budget::new(
+            Some(Duration::from_secs_f64(self.word_timeout)),
+            self.total_timeout.map(Duration::from_secs_f64),
+            Some(self.max_configurations),
+        )
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub struct AlphabetArgs {
+    /// Alphabet for the generated words, like "ab" or "{a, b}" [default: union of both alphabets]
+    #[arg(long = "alphabet")]
+    pub alphabet: Option<String>,
+
+    /// File with the alphabet for the generated words in its first line, like "Σ = {a, b}"
+    #[arg(long = "alphabet-file", conflicts_with = "alphabet")]
+    pub alphabet_file: Option<String>,
+}
+
+impl AlphabetArgs {
+    pub fn alphabet(&self) -> Result<Option<Vec<char>>, ParseError> {
+        if let Some(path) = &self.alphabet_file {
+            let file = fs::read_to_string(path)
+                .map_err(|error| ParseError(format!("could not read {}: {}", path, error)))?;
+            let header = file.lines().next().unwrap_or_default().trim();
+            let declaration = match header.strip_prefix('Σ') {
+                Some(declaration) => declaration.trim_start().trim_start_matches('='),
+                None => header,
+            };
+            return Ok(Some(parse_alphabet(declaration)));
+        }
+        Ok(self.alphabet.as_deref().map(parse_alphabet))
+    }
+
+    // the default generation settings with this alphabet
+    pub fn generation_config(&self) -> Result<GenerationConfig, ParseError> {
+        Ok(GenerationConfig {
+            alphabet: self.alphabet()?,
+            ..GenerationConfig::default()
+        })
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub struct GenerationArgs {
+    /// Test all words up to this length in the generated comparison [default: 7]
+    #[arg(long = "exhaustive-length")]
+    pub exhaustive_length: Option<usize>,
+
+    /// Maximum number of exhaustively tested words, larger alphabets get a lower length [default: 100000]
+    #[arg(long = "max-exhaustive-words")]
+    pub max_exhaustive_words: Option<usize>,
+
+    /// Number of random words in the generated comparison [default: 100000]
+    #[arg(long = "random-samples")]
+    pub random_samples: Option<usize>,
+
+    /// Minimum length of random words [default: 0]
+    #[arg(long = "random-min-length")]
+    pub random_min_length: Option<usize>,
+
+    /// Maximum length of random words (exclusive) [default: 25]
+    #[arg(long = "random-max-length")]
+    pub random_max_length: Option<usize>,
+
+    /// How the lengths of random words are distributed [default: uniform]
+    #[arg(long = "length-distribution", value_enum)]
+    pub length_distribution: Option<LengthDistribution>,
+
+    /// Comma separated weights for the lengths of random words, starting at length 0
+    /// (replaces the length range and distribution)
+    #[arg(long = "length-weights", value_delimiter = ',')]
+    pub length_weights: Vec<f64>,
+
+    /// Probability for a random word to be a small change of an earlier one,
+    /// to test more words near the boundary of the language [default: 0]
+    #[arg(long = "boundary-bias")]
+    pub boundary_bias: Option<f64>,
+
+    /// Seed for the random words [default: 42]
+    #[arg(long = "seed")]
+    pub seed: Option<u64>,
+
+    /// Words up to this length are considered for the graded score of a comparison [default: 20]
+    #[arg(long = "score-length")]
+    pub score_length: Option<usize>,
+
+    /// How the language of the first automaton has to relate to the second one [default: equal]
+    #[arg(long = "relation", value_enum)]
+    pub relation: Option<Relation>,
+
+    /// Number of threads that simulate words at the same time, 0 uses all cores
+    /// (batch grades one submission per thread) [default: 0]
+    #[arg(long = "threads")]
+    pub threads: Option<usize>,
+
+    #[command(flatten)]
+    pub alphabet: AlphabetArgs,
+}
+
+impl GenerationArgs {
+    pub fn generation_config(&self) -> Result<GenerationConfig, ParseError> {
+        let default = GenerationConfig::default();
+        let config = GenerationConfig {
+            exhaustive_max_length: self
+                .exhaustive_length
+                .unwrap_or(default.exhaustive_max_length),
+            max_exhaustive_words: self
+                .max_exhaustive_words
+                .unwrap_or(default.max_exhaustive_words),
+            random_samples: self.random_samples.unwrap_or(default.random_samples),
+            random_lengths: self
+                .random_min_length
+                .unwrap_or(default.random_lengths.start)
+                ..self.random_max_length.unwrap_or(default.random_lengths.end),
+            distribution: self.length_distribution.unwrap_or(default.distribution),
+            length_weights: self.length_weights.clone(),
+            boundary_bias: self.boundary_bias.unwrap_or(default.boundary_bias),
+            seed: self.seed.unwrap_or(default.seed),
+            score_max_length: self.score_length.unwrap_or(default.score_max_length),
+            alphabet: self.alphabet.alphabet()?,
+            relation: self.relation.unwrap_or(default.relation),
+            threads: self.threads.unwrap_or(default.threads),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subcommands() {
+        let cli = Cli::try_parse_from([
+            "automatonator",
+            "grade",
+            "a-dfa.txt",
+            "b-dfa.txt",
+            "eval.txt",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::Grade(args) => {
+                assert_eq!(args.automatons.automaton2, "b-dfa.txt");
+                assert_eq!(args.evaluation_file, "eval.txt");
+            }
+            command => panic!("parsed {:?}", command),
+        }
+        assert!(Cli::try_parse_from(["automatonator", "compare", "a-dfa.txt"]).is_err());
+        let legacy =
+            LegacyArgs::try_parse_from(["automatonator", "a-dfa.txt", "-c", "words.txt"]).unwrap();
+        assert_eq!(legacy.testcase_file.as_deref(), Some("words.txt"));
+    }
+
+    #[test]
+    fn test_alphabet() {
+        let args = |alphabet: Option<&str>, alphabet_file: Option<&str>| AlphabetArgs {
+            alphabet: alphabet.map(str::to_string),
+            alphabet_file: alphabet_file.map(str::to_string),
+        };
+        assert_eq!(
+            args(Some("{a, b}"), None).alphabet(),
+            Ok(Some(vec!['a', 'b']))
+        );
+        assert_eq!(
+            args(None, Some("data/dfa/ab-alphabet.txt")).alphabet(),
+            Ok(Some(vec!['a', 'b']))
+        );
+        assert!(args(None, Some("data/dfa/missing.txt")).alphabet().is_err());
+        assert_eq!(args(None, None).alphabet(), Ok(None));
+    }
+
+    #[test]
+    fn test_timeouts() {
+        let check = |timeout: &str| {
+            Cli::try_parse_from(["automatonator", "check", "a-dfa.txt", "words.txt", timeout])
+        };
+        assert!(check("--word-timeout=0.5").is_ok());
+        assert!(check("--word-timeout=-1").is_err());
+        assert!(check("--word-timeout=NaN").is_err());
+        assert!(check("--total-timeout=-1").is_err());
+        assert!(check("--total-timeout=inf").is_err());
+    }
 }
@@ -0,0 +1,210 @@
+use log::info;
+use log::warn;
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use crate::automatons::nfa::NFA;
+use crate::shared::automaton::*;
+use crate::shared::budget::{Exceeded, Limit};
+
+// Büchi automaton: an nfa that reads infinite words and accepts them
+// if a run visits a final state infinitely often
+// Words are ultimately periodic and written as u(v), meaning u followed by v repeated forever
+pub struct Buchi {
+    nfa: NFA,
+}
+
+impl Buchi {
+    // An accepted word, None if the language is empty
+    pub fn find_lasso(&self) -> Option<String> {
+        let prefixes = shortest_words(self.nfa.start_states().iter().copied(), &self.nfa);
+        let mut finals: Vec<_> = self
+            .nfa
+            .final_states()
+            .iter()
+            .filter(|state| prefixes.contains_key(state))
+            .collect();
+        finals.sort();
+        finals.into_iter().find_map(|&state| {
+            // shortest way back to the final state, taking at least one step
+            let cycle = self
+                .sorted_neighbours(state)
+                .into_iter()
+                .filter_map(|(symbol, target)| {
+                    shortest_words([target], &self.nfa)
+                        .get(&state)
+                        .map(|rest| format!("{}{}", symbol, rest))
+                })
+                .min_by_key(|cycle| cycle.chars().count())?;
+            Some(format!("{}({})", prefixes[&state], cycle))
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.find_lasso().is_none()
+    }
+
+    fn sorted_neighbours(&self, state: VertexId) -> Vec<(char, VertexId)> {
+        let mut neighbours: Vec<_> = self
+            .nfa
+            .neighbours(state)
+            .filter(|(symbol, _)| *symbol != ' ')
+            .collect();
+        neighbours.sort();
+        neighbours
+    }
+
+    pub fn new(data: Vec<AutomatonData>) -> Result<Buchi, ParseError> {
+        let nfa = NFA::new(data)?;
+        // runs are stepped symbol by symbol, an epsilon cycle would count as an infinite run
+        if nfa.has_epsilon() {
+            return Err(ParseError::new(
+                "büchi automatons can't have epsilon transitions",
+            ));
+        }
+        Ok(Buchi { nfa })
+    }
+}
+
+impl Automaton for Buchi {
+    fn accepts_within(&self, word: &str, limit: &mut Limit) -> Result<bool, Exceeded> {
+        let Some((u, v)) = parse_lasso(word) else {
+            warn!("'{}' is not an infinite word of the form u(v)", word);
+            return Ok(false);
+        };
+        // all states that can be reached after reading u
+        let mut currents: HashSet<VertexId> = self.nfa.start_states().iter().copied().collect();
+        for symbol in u.chars() {
+            let mut next = HashSet::new();
+            for state in currents {
+                for target in self.nfa.successors(state, symbol) {
+                    limit.step()?;
+                    next.insert(*target);
+                }
+            }
+            currents = next;
+        }
+        // afterwards, the configurations are a state and a position in v
+        let v: Vec<char> = v.chars().collect();
+        let len = v.len();
+        let step = |&(state, i): &(VertexId, usize)| {
+            self.nfa
+                .successors(state, v[i])
+                .iter()
+                .map(move |target| (*target, (i + 1) % len))
+        };
+        let reachable = explore(currents.into_iter().map(|state| (state, 0)), step, limit)?;
+        // the word is accepted if a reachable final state lies on a cycle
+        for node in reachable.iter() {
+            if self.nfa.final_states().contains(&node.0)
+                && explore(step(node), step, limit)?.contains(node)
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn alphabet(&self) -> &[char] {
+        self.nfa.alphabet()
+    }
+
+    fn view(&self) {
+        let mut out = self.nfa.describe("Büchi");
+        match self.find_lasso() {
+            Some(word) => out.push_str(&format!("\nAccepts for example: {}", word)),
+            None => out.push_str("\nThe language is empty"),
+        }
+        info!("{}", out);
+    }
+
+    fn states(&self) -> Vec<VertexId> {
+        self.nfa.states()
+    }
+
+    fn transitions(&self) -> Vec<(VertexId, VertexId, String)> {
+        self.nfa.transitions()
+    }
+
+    fn shortest_word(&self) -> Option<Option<String>> {
+        Some(self.find_lasso())
+    }
+
+    fn is_omega(&self) -> bool {
+        true
+    }
+}
+
+// Splits u(v) into u and v, a word without parentheses is read as (word)
+pub fn parse_lasso(word: &str) -> Option<(&str, &str)> {
+    let (u, v) = match word.strip_suffix(')') {
+        Some(rest) => rest.rsplit_once('(')?,
+        None => ("", word),
+    };
+    if v.is_empty() {
+        None
+    } else {
+        Some((u, v))
+    }
+}
+
+// All nodes reachable from the start nodes (including them)
+fn explore<N, I>(
+    start: impl IntoIterator<Item = N>,
+    step: impl Fn(&N) -> I,
+    limit: &mut Limit,
+) -> Result<HashSet<N>, Exceeded>
+where
+    N: Copy + Eq + std::hash::Hash,
+    I: Iterator<Item = N>,
+{
+    let mut seen: HashSet<N> = HashSet::new();
+    let mut queue: VecDeque<N> = VecDeque::new();
+    for node in start {
+        if seen.insert(node) {
+            queue.push_back(node);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        for next in step(&node) {
+            limit.step()?;
+            if seen.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    Ok(seen)
+}
+
+// The shortest word leading to every state reachable from the start states
+fn shortest_words(
+    start: impl IntoIterator<Item = VertexId>,
+    nfa: &NFA,
+) -> HashMap<VertexId, String> {
+    let mut words: HashMap<VertexId, String> = HashMap::new();
+    let mut queue = VecDeque::new();
+    for state in start {
+        if let Entry::Vacant(entry) = words.entry(state) {
+            entry.insert(String::new());
+            queue.push_back(state);
+        }
+    }
+    while let Some(state) = queue.pop_front() {
+        let mut neighbours: Vec<_> = nfa
+            .neighbours(state)
+            .filter(|(symbol, _)| *symbol != ' ')
+            .collect();
+        neighbours.sort();
+        for (symbol, target) in neighbours {
+            if !words.contains_key(&target) {
+                let word = format!("{}{}", words[&state], symbol);
+                words.insert(target, word);
+                queue.push_back(target);
+            }
+        }
+    }
+    words
+}
@@ -0,0 +1,283 @@
+use log::info;
+use log::warn;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::fmt;
+use std::iter::once;
+
+use crate::shared::automaton::*;
+use crate::shared::budget::{Exceeded, Limit};
+use crate::shared::utils::declared_alphabet;
+use crate::shared::utils::format_states;
+use crate::shared::utils::logcheck_e;
+use crate::shared::utils::logcheck_w;
+use crate::shared::utils::parse_char;
+use crate::shared::utils::sorted_states;
+use crate::shared::utils::symbol_label;
+
+type Destinations = Vec<(VertexId, Vec<Operation>)>;
+type Configuration = (VertexId, usize, Vec<u64>);
+
+// Automaton with counters instead of a stack, every transition reads a symbol (or epsilon)
+// and tests or changes the counters, which start at 0 and can't become negative
+pub struct Counter {
+    states: HashMap<VertexId, HashMap<char, Destinations>>,
+    alphabet: Vec<char>,
+    final_states: Vec<VertexId>,
+    start_states: Vec<VertexId>,
+    counters: usize,
+}
+
+// Operation on a counter (counted from 0)
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    IsZero(usize),
+    NotZero(usize),
+    Change(usize, i64),
+}
+
+impl Counter {
+    // without final states, a word is accepted if all counters are 0 at the end
+    fn accepted(&self, state: VertexId, counters: &[u64]) -> bool {
+        if self.final_states.is_empty() {
+            counters.iter().all(|c| *c == 0)
+        } else {
+            self.final_states.contains(&state)
+        }
+    }
+
+    pub fn new(data: Vec<AutomatonData>) -> Result<Counter, ParseError> {
+        let mut states = HashMap::new();
+        let mut alphabet = HashSet::new();
+        let mut final_states = HashSet::new();
+        let mut start_states = HashSet::new();
+        let mut declared = None;
+        let mut counters = 0;
+        for d in data {
+            match d {
+                AutomatonData::Edge(source, target, label) => {
+                    let mut values = label.split(",");
+                    let missing =
+                        |what: &str| ParseError(format!("No {} given in '{}'", what, label));
+                    let symbol = parse_char(values.next().ok_or(missing("Character"))?);
+                    let operations: Vec<Operation> = values
+                        .map(|value| {
+                            parse_operation(value).ok_or(ParseError(format!(
+                                "'{}' is not a counter operation in '{}'",
+                                value, label
+                            )))
+                        })
+                        .collect::<Result<_, _>>()?;
+                    for operation in operations.iter() {
+                        counters = counters.max(operation.counter() + 1);
+                    }
+                    alphabet.insert(symbol);
+                    states
+                        .entry(source)
+                        .or_insert(HashMap::new())
+                        .entry(symbol)
+                        .or_insert(Vec::new())
+                        .push((target, operations));
+                }
+                AutomatonData::Final(id) => {
+                    final_states.insert(id);
+                }
+                AutomatonData::Start(id) => {
+                    start_states.insert(id);
+                }
+                AutomatonData::Alphabet(symbols) => declared = Some(symbols),
+                AutomatonData::StackAlphabet(_) => {
+                    warn!("ignoring stack alphabet of a counter automaton")
+                }
+                AutomatonData::Output(..)
+                | AutomatonData::Threshold(_)
+                | AutomatonData::Name(..) => {}
+            }
+        }
+        logcheck_e(start_states.is_empty(), "No start state given")?;
+        logcheck_w(counters == 0, "No counter is used");
+        Ok(Counter {
+            states,
+            alphabet: declared_alphabet(declared, alphabet, "alphabet"),
+            final_states: final_states.into_iter().collect(),
+            start_states: start_states.into_iter().collect(),
+            counters,
+        })
+    }
+}
+
+impl Automaton for Counter {
+    fn accepts_within(&self, word: &str, limit: &mut Limit) -> Result<bool, Exceeded> {
+        let word: Vec<char> = word.chars().collect();
+        // initialize state
+        let mut seen: HashSet<Configuration> = self
+            .start_states
+            .iter()
+            .map(|state| (*state, 0, vec![0; self.counters]))
+            .collect();
+        let mut currents: VecDeque<_> = seen.iter().cloned().collect();
+
+        while let Some((state, position, counters)) = currents.pop_front() {
+            if position == word.len() && self.accepted(state, &counters) {
+                return Ok(true);
+            }
+            // epsilon transitions and the next symbol of the word
+            for symbol in [Some(' '), word.get(position).copied()]
+                .into_iter()
+                .flatten()
+            {
+                let read = usize::from(symbol != ' ');
+                let destinations = self
+                    .states
+                    .get(&state)
+                    .and_then(|s| s.get(&symbol))
+                    .map(Vec::as_slice)
+                    .unwrap_or(&[]);
+                for (target, operations) in destinations {
+                    if let Some(counters) = apply(operations, &counters) {
+                        let next = (*target, position + read, counters);
+                        // only apply state if it hasnt been seen yet
+                        if !seen.contains(&next) {
+                            limit.step()?;
+                            seen.insert(next.clone());
+                            currents.push_back(next);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    fn view(&self) {
+        let mut out = String::new();
+        out.push_str("Type: Counter");
+        out.push_str(&format!("\nCounters: {}", self.counters));
+        out.push_str(&format!(
+            "\nFinal States: {}",
+            format_states(&self.final_states)
+        ));
+        out.push_str(&format!(
+            "\nStart States: {}",
+            format_states(&self.start_states)
+        ));
+        let mut states: Vec<_> = self.states.iter().collect();
+        states.sort_by_key(|&(key, _)| key);
+        states.iter().for_each(|(id, map)| {
+            out.push_str(&format!("\nState {}:", id));
+            map.iter().for_each(|(label, destinations)| {
+                destinations.iter().for_each(|(target, operations)| {
+                    let operations: String =
+                        operations.iter().map(|op| format!(",{}", op)).collect();
+                    out.push_str(&format!("\n    {}{} -> {}", label, operations, target))
+                })
+            });
+        });
+        info!("{}", out);
+    }
+
+    fn states(&self) -> Vec<VertexId> {
+        sorted_states(
+            self.states
+                .iter()
+                .flat_map(|(id, map)| {
+                    once(*id).chain(map.values().flatten().map(|(target, _)| *target))
+                })
+                .chain(self.start_states.iter().copied())
+                .chain(self.final_states.iter().copied()),
+        )
+    }
+
+    fn transitions(&self) -> Vec<(VertexId, VertexId, String)> {
+        self.states
+            .iter()
+            .flat_map(|(id, map)| {
+                map.iter().flat_map(move |(symbol, destinations)| {
+                    destinations.iter().map(move |(target, operations)| {
+                        let operations: String =
+                            operations.iter().map(|op| format!(",{}", op)).collect();
+                        (
+                            *id,
+                            *target,
+                            format!("{}{}", symbol_label(*symbol), operations),
+                        )
+                    })
+                })
+            })
+            .collect()
+    }
+}
+
+impl Operation {
+    fn counter(&self) -> usize {
+        match self {
+            Operation::IsZero(c) | Operation::NotZero(c) | Operation::Change(c, _) => *c,
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::IsZero(c) => write!(f, "c{}=0", c + 1),
+            Operation::NotZero(c) => write!(f, "c{}>0", c + 1),
+            Operation::Change(c, n) => write!(f, "c{}{:+}", c + 1, n),
+        }
+    }
+}
+
+// The counters after applying all operations in order, None if a test fails
+// or a counter would become negative
+fn apply(operations: &[Operation], counters: &[u64]) -> Option<Vec<u64>> {
+    let mut counters = counters.to_vec();
+    for operation in operations {
+        match *operation {
+            Operation::IsZero(c) if counters[c] != 0 => return None,
+            Operation::NotZero(c) if counters[c] == 0 => return None,
+            Operation::Change(c, n) => {
+                counters[c] = counters[c].checked_add_signed(n)?;
+            }
+            _ => {}
+        }
+    }
+    Some(counters)
+}
+
+// Parses operations like "=0", ">0", "+1", "-2" or "c2+1" (for the second counter)
+fn parse_operation(input: &str) -> Option<Operation> {
+    let input = input.trim();
+    let (counter, operation) = match input.strip_prefix('c') {
+        Some(rest) => {
+            let split = rest.find(|c: char| !c.is_ascii_digit())?;
+            let counter: usize = rest[..split].parse().ok()?;
+            (counter.checked_sub(1)?, &rest[split..])
+        }
+        None => (0, input),
+    };
+    match operation {
+        "=0" | "==0" => Some(Operation::IsZero(counter)),
+        ">0" | "!=0" | "≠0" => Some(Operation::NotZero(counter)),
+        "+" | "++" => Some(Operation::Change(counter, 1)),
+        "-" | "--" => Some(Operation::Change(counter, -1)),
+        _ if operation.starts_with(['+', '-']) => {
+            Some(Operation::Change(counter, operation.parse().ok()?))
+        }
+        _ => None,
+    }
+}
+
+#[test]
+fn test_parse_operation() {
+    assert!(parse_operation("=0") == Some(Operation::IsZero(0)));
+    assert!(parse_operation(" c2>0") == Some(Operation::NotZero(1)));
+    assert!(parse_operation("+2") == Some(Operation::Change(0, 2)));
+    assert!(parse_operation("c3-1") == Some(Operation::Change(2, -1)));
+    assert!(parse_operation("c0+1").is_none());
+    assert!(parse_operation("*2").is_none());
+}
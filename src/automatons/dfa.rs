@@ -3,11 +3,19 @@ use log::warn;
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::iter::once;
 
 use crate::shared::automaton::*;
+use crate::shared::budget::{Exceeded, Limit};
+use crate::shared::regular::{RegularDfa, MAX_STATES};
+use crate::shared::utils::declared_alphabet;
+use crate::shared::utils::format_states;
 use crate::shared::utils::logcheck_e;
 use crate::shared::utils::logcheck_w;
-use crate::shared::utils::parse_char;
+use crate::shared::utils::parse_symbols;
+use crate::shared::utils::sorted_states;
+use crate::shared::utils::symbol_label;
+use crate::shared::utils::LabelSymbol;
 
 #[allow(clippy::upper_case_acronyms)]
 pub struct DFA {
@@ -18,29 +26,121 @@ pub struct DFA {
 }
 
 impl DFA {
-    pub fn accepts(&self, word: &str) -> bool {
+    pub fn new(data: Vec<AutomatonData>) -> Result<DFA, ParseError> {
+        let mut states = HashMap::new();
+        let mut alphabet = HashSet::new();
+        let mut final_states = HashSet::new();
+        let mut start_state = 0;
+        let mut declared = None;
+        let mut else_edges = Vec::new();
+        for d in data {
+            match d {
+                AutomatonData::Edge(source, target, label) => {
+                    for symbol in parse_symbols(&label)? {
+                        let label = match symbol {
+                            LabelSymbol::Symbol(c) => c,
+                            LabelSymbol::Epsilon => {
+                                warn!("epsilon transition in a dfa");
+                                ' '
+                            }
+                            // a dfa can only have one transition per symbol, so both mean the same
+                            LabelSymbol::Else | LabelSymbol::Any => {
+                                else_edges.push((source, target));
+                                continue;
+                            }
+                        };
+                        alphabet.insert(label);
+                        states
+                            .entry(source)
+                            .or_insert(HashMap::new())
+                            .insert(label, target);
+                    }
+                }
+                AutomatonData::Final(id) => {
+                    final_states.insert(id);
+                }
+                AutomatonData::Start(id) => {
+                    if start_state != 0 {
+                        warn!("multiple start states in a dfa, overwriting")
+                    };
+                    start_state = id;
+                }
+                AutomatonData::Alphabet(symbols) => declared = Some(symbols),
+                AutomatonData::StackAlphabet(_) => warn!("ignoring stack alphabet of a dfa"),
+                AutomatonData::Output(..)
+                | AutomatonData::Threshold(_)
+                | AutomatonData::Name(..) => {}
+            }
+        }
+
+        logcheck_e(states.is_empty(), "No states given")?;
+        logcheck_w(final_states.is_empty(), "No final states given");
+        logcheck_e(start_state == 0, "No start state given")?;
+
+        let is_declared = declared.is_some();
+        let alphabet = declared_alphabet(declared, alphabet, "alphabet");
+        for (source, target) in else_edges {
+            let map = states.entry(source).or_insert(HashMap::new());
+            for symbol in alphabet.iter() {
+                map.entry(*symbol).or_insert(target);
+            }
+        }
+        if is_declared {
+            check_complete(&states, start_state, &alphabet);
+        }
+
+        Ok(DFA {
+            states,
+            alphabet,
+            final_states: final_states.into_iter().collect(),
+            start_state,
+        })
+    }
+}
+
+impl Automaton for DFA {
+    fn accepts_within(&self, word: &str, limit: &mut Limit) -> Result<bool, Exceeded> {
         let mut current = &self.start_state;
-        let mut encountered_missing_edge = false;
-        word.chars().for_each(|symbol: char| {
+        for symbol in word.chars() {
+            limit.step()?;
             if let Some(next) = self.states.get(current).and_then(|s| s.get(&symbol)) {
                 current = next;
             } else {
-                encountered_missing_edge = true;
+                // missing edge, the word can't be accepted anymore
+                return Ok(false);
             }
-        });
-        self.final_states.contains(current) && !encountered_missing_edge
+        }
+        Ok(self.final_states.contains(current))
+    }
+
+    fn trace(&self, word: &str) -> Option<Vec<String>> {
+        let mut current = self.start_state;
+        let mut steps = vec![format!("start in {}", current)];
+        for symbol in word.chars() {
+            match self.states.get(&current).and_then(|s| s.get(&symbol)) {
+                Some(next) => {
+                    steps.push(format!("{} -{}-> {}", current, symbol, next));
+                    current = *next;
+                }
+                None => {
+                    steps.push(format!("{} -{}-> no transition", current, symbol));
+                    break;
+                }
+            }
+        }
+        Some(steps)
     }
 
-    pub fn view(&self) {
+    fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    fn view(&self) {
         let mut out = String::new();
         out.push_str("Type: DFA");
         out.push_str(&format!(
             "\nFinal States: {}",
-            self.final_states
-                .iter()
-                .map(|id| id.to_string())
-                .reduce(|acc, id| format!("{acc}, {id}"))
-                .expect("Automaton should have at least 1 final state")
+            format_states(&self.final_states)
         ));
         out.push_str(&format!("\nStart State: {}", &self.start_state));
         let mut states: Vec<_> = self.states.iter().collect();
@@ -53,44 +153,70 @@ impl DFA {
         });
         info!("{}", out);
     }
-    pub fn new(data: Vec<AutomatonData>) -> DFA {
-        let mut states = HashMap::new();
-        let mut alphabet = HashSet::new();
-        let mut final_states = HashSet::new();
-        let mut start_state = 0;
-        data.into_iter().for_each(|d| match d {
-            AutomatonData::Edge(source, target, label) => {
-                let label = parse_char(&label);
-                alphabet.insert(label);
-                states
-                    .entry(source)
-                    .or_insert(HashMap::new())
-                    .insert(label, target);
-            }
-            AutomatonData::Final(id) => {
-                final_states.insert(id);
-            }
-            AutomatonData::Start(id) => {
-                if start_state != 0 {
-                    warn!("multiple start states in a dfa, overwriting")
-                };
-                start_state = id;
-            }
-        });
 
-        logcheck_e(states.is_empty(), "No states given");
-        logcheck_w(final_states.is_empty(), "No final states given");
-        logcheck_e(start_state == 0, "No start state given");
+    fn to_regular(&self, alphabet: &[char]) -> Option<RegularDfa> {
+        // None is the implicit trap state for missing edges
+        RegularDfa::explore(
+            alphabet,
+            Some(self.start_state),
+            |state, symbol| {
+                state.and_then(|state| {
+                    self.states
+                        .get(&state)
+                        .and_then(|s| s.get(&symbol))
+                        .copied()
+                })
+            },
+            |state| state.is_some_and(|state| self.final_states.contains(&state)),
+            MAX_STATES,
+        )
+    }
 
-        DFA {
-            states,
-            alphabet: alphabet.into_iter().collect(),
-            final_states: final_states.into_iter().collect(),
-            start_state,
-        }
+    fn states(&self) -> Vec<VertexId> {
+        sorted_states(
+            self.states
+                .iter()
+                .flat_map(|(id, map)| once(*id).chain(map.values().copied()))
+                .chain(once(self.start_state))
+                .chain(self.final_states.iter().copied()),
+        )
     }
 
-    pub fn alphabet(&self) -> &Vec<char> {
-        &self.alphabet
+    fn transitions(&self) -> Vec<(VertexId, VertexId, String)> {
+        self.states
+            .iter()
+            .flat_map(|(id, map)| {
+                map.iter()
+                    .map(|(symbol, target)| (*id, *target, symbol_label(*symbol)))
+            })
+            .collect()
+    }
+}
+
+// Warns about every state that is missing a transition for a symbol of the declared alphabet
+fn check_complete(
+    states: &HashMap<VertexId, HashMap<char, VertexId>>,
+    start_state: VertexId,
+    alphabet: &[char],
+) {
+    let mut all_states: Vec<_> = states
+        .iter()
+        .flat_map(|(id, map)| once(*id).chain(map.values().copied()))
+        .chain(once(start_state))
+        .collect();
+    all_states.sort();
+    all_states.dedup();
+    for state in all_states {
+        for symbol in alphabet {
+            logcheck_w(
+                !states
+                    .get(&state)
+                    .is_some_and(|map| map.contains_key(symbol)),
+                &format!(
+                    "State {} has no transition for '{}', the dfa is not complete",
+                    state, symbol
+                ),
+            );
+        }
     }
 }
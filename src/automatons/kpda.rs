@@ -1,12 +1,17 @@
 use log::info;
 
 use crate::shared::automaton::*;
+use crate::shared::budget::{Exceeded, Limit};
 use crate::shared::utils::format_states;
 use crate::shared::utils::logcheck_e;
 use crate::shared::utils::parse_char;
+use crate::shared::utils::sorted_alphabet;
+use crate::shared::utils::sorted_states;
+use crate::shared::utils::symbol_label;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::iter::once;
 
 type StackChar = char;
 type Destinations = Vec<(VertexId, Stacks)>;
@@ -22,7 +27,86 @@ pub struct KPDA {
 }
 
 impl KPDA {
-    pub fn accepts(&self, word: &str) -> bool {
+    // helper to check for given automaton state if it is accepted
+    fn accepted(&self, currents: &VecDeque<(VertexId, Stacks)>) -> bool {
+        if self.final_states.is_empty() {
+            currents.iter().any(|s| s.1.all_empty())
+        } else {
+            currents.iter().any(|s| self.final_states.contains(&s.0))
+        }
+    }
+
+    pub fn new(data: Vec<AutomatonData>) -> Result<KPDA, ParseError> {
+        let mut states = HashMap::new();
+        let mut alphabet = HashSet::new();
+        let mut final_states = HashSet::new();
+        let mut start_states = HashSet::new();
+        let mut k = 0;
+        for d in data {
+            match d {
+                AutomatonData::Edge(source, target, label) => {
+                    // info!("{label}");
+                    let mut values = label.split(",");
+                    let mut current_stacks = Vec::new();
+                    let mut next_stacks = Vec::new();
+                    let mut k_i = 0;
+                    while let Some(stack_char) = values.next() {
+                        k_i += 1;
+                        current_stacks.push(parse_char(stack_char));
+                        next_stacks.push(
+                            values
+                                .next()
+                                .ok_or(ParseError::new(
+                                    "Even Number of Values in PDA Label not supported",
+                                ))?
+                                .to_owned(),
+                        );
+                    }
+
+                    if k == 0 {
+                        k = k_i
+                    }
+
+                    for c in current_stacks.iter() {
+                        if c.is_lowercase() {
+                            alphabet.insert(*c);
+                        }
+                    }
+
+                    logcheck_e(k != k_i, "Number of stacks not consistent.")?;
+                    states
+                        .entry(source)
+                        .or_insert(HashMap::new())
+                        .entry(current_stacks)
+                        .or_insert(Vec::new())
+                        .push((target, Stacks::from_data(next_stacks)));
+                }
+                AutomatonData::Final(id) => {
+                    final_states.insert(id);
+                }
+                AutomatonData::Start(id) => {
+                    start_states.insert(id);
+                }
+                AutomatonData::Alphabet(symbols) => alphabet.extend(symbols),
+                AutomatonData::StackAlphabet(_)
+                | AutomatonData::Output(..)
+                | AutomatonData::Threshold(_)
+                | AutomatonData::Name(..) => {}
+            }
+        }
+        logcheck_e(start_states.is_empty(), "No start state given")?;
+        Ok(KPDA {
+            states,
+            alphabet: sorted_alphabet(alphabet),
+            final_states: final_states.into_iter().collect(),
+            start_states: start_states.into_iter().collect(),
+            k: k.max(1),
+        })
+    }
+}
+
+impl Automaton for KPDA {
+    fn accepts_within(&self, word: &str, limit: &mut Limit) -> Result<bool, Exceeded> {
         // initialize state
         let mut seen: Vec<(VertexId, Stacks)> = self
             .start_states
@@ -33,12 +117,14 @@ impl KPDA {
         let mut currents: VecDeque<_> = seen.clone().into();
 
         // check after every step if we accepted, or no new state was found
-        while self.accepted(&currents) && !currents.is_empty() {
+        while !self.accepted(&currents) && !currents.is_empty() {
             // take steps for each current state
             for _ in 0..currents.len() {
                 let current = currents.pop_front().unwrap();
                 let stacks = current.1;
-                let transitions = self.states.get(&current.0).unwrap();
+                let Some(transitions) = self.states.get(&current.0) else {
+                    continue;
+                };
                 for next_key in transitions.keys() {
                     // check if transition can be applied to current stacks
                     if stacks.fits(next_key) {
@@ -47,6 +133,7 @@ impl KPDA {
                             let next = (next.0, stacks.clone().apply(&next.1));
                             // only apply state if it hasnt been seen yet
                             if !seen.contains(&next) {
+                                limit.step()?;
                                 seen.push(next.clone());
                                 currents.push_back(next);
                             }
@@ -56,19 +143,14 @@ impl KPDA {
             }
         }
         // return if while broke because it accepted or because no new currents
-        self.accepted(&currents)
+        Ok(self.accepted(&currents))
     }
 
-    // helper to check for given automaton state if it is accepted
-    fn accepted(&self, currents: &VecDeque<(VertexId, Stacks)>) -> bool {
-        if self.final_states.is_empty() {
-            currents.iter().any(|s| s.1.all_empty())
-        } else {
-            currents.iter().any(|s| self.final_states.contains(&s.0))
-        }
+    fn alphabet(&self) -> &[char] {
+        &self.alphabet
     }
 
-    pub fn view(&self) {
+    fn view(&self) {
         let mut out = String::new();
         out.push_str("Type: PDA");
         out.push_str(&format!(
@@ -93,67 +175,35 @@ impl KPDA {
         });
         info!("{}", out);
     }
-    pub fn new(data: Vec<AutomatonData>) -> KPDA {
-        let mut states = HashMap::new();
-        let mut alphabet = HashSet::new();
-        let mut final_states = HashSet::new();
-        let mut start_states = HashSet::new();
-        let mut k = 0;
-        data.into_iter().for_each(|d| match d {
-            AutomatonData::Edge(source, target, label) => {
-                // info!("{label}");
-                let mut values = label.split(",");
-                let mut current_stacks = Vec::new();
-                let mut next_stacks = Vec::new();
-                let mut k_i = 0;
-                while let Some(stack_char) = values.next() {
-                    k_i += 1;
-                    current_stacks.push(parse_char(stack_char));
-                    next_stacks.push(
-                        values
-                            .next()
-                            .expect("Even Number of Values in PDA Label not supported")
-                            .to_owned(),
-                    );
-                }
 
-                if k == 0 {
-                    k = k_i
-                }
-
-                for c in current_stacks.iter() {
-                    if c.is_lowercase() {
-                        alphabet.insert(*c);
-                    }
-                }
-
-                logcheck_e(k == k_i, "Number of stacks not consistent.");
-                states
-                    .entry(source)
-                    .or_insert(HashMap::new())
-                    .entry(current_stacks)
-                    .or_insert(Vec::new())
-                    .push((target, Stacks::from_data(next_stacks)));
-            }
-            AutomatonData::Final(id) => {
-                final_states.insert(id);
-            }
-            AutomatonData::Start(id) => {
-                start_states.insert(id);
-            }
-        });
-        assert!(!start_states.is_empty(), "No start state given");
-        KPDA {
-            states,
-            alphabet: alphabet.into_iter().collect(),
-            final_states: final_states.into_iter().collect(),
-            start_states: start_states.into_iter().collect(),
-            k: 1,
-        }
+    fn states(&self) -> Vec<VertexId> {
+        sorted_states(
+            self.states
+                .iter()
+                .flat_map(|(id, map)| {
+                    once(*id).chain(map.values().flatten().map(|(target, _)| *target))
+                })
+                .chain(self.start_states.iter().copied())
+                .chain(self.final_states.iter().copied()),
+        )
     }
 
-    pub fn alphabet(&self) -> &Vec<char> {
-        &self.alphabet
+    fn transitions(&self) -> Vec<(VertexId, VertexId, String)> {
+        self.states
+            .iter()
+            .flat_map(|(id, map)| {
+                map.iter().flat_map(move |(tops, destinations)| {
+                    destinations.iter().map(move |(target, stacks)| {
+                        let label: Vec<String> = tops
+                            .iter()
+                            .zip(stacks.data.iter())
+                            .map(|(top, push)| format!("{},{}", symbol_label(*top), push))
+                            .collect();
+                        (*id, *target, label.join(","))
+                    })
+                })
+            })
+            .collect()
     }
 }
 
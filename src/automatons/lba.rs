@@ -0,0 +1,220 @@
+use log::info;
+use log::warn;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::iter::once;
+
+use crate::shared::automaton::*;
+use crate::shared::budget::{Exceeded, Limit};
+use crate::shared::utils::*;
+
+type Transition = (VertexId, char, Move);
+type Configuration = (VertexId, usize, Vec<char>);
+
+// Linear bounded automaton: a nondeterministic turing machine that can only use
+// the cells of its input between the end markers < and >
+// The head starts on the first symbol, the word is accepted if a run halts in a final state
+#[allow(clippy::upper_case_acronyms)]
+pub struct LBA {
+    states: HashMap<VertexId, HashMap<char, Vec<Transition>>>,
+    alphabet: Vec<char>,
+    final_states: Vec<VertexId>,
+    start_states: Vec<VertexId>,
+}
+
+impl LBA {
+    pub fn new(data: Vec<AutomatonData>) -> Result<LBA, ParseError> {
+        let mut states = HashMap::new();
+        let mut read_symbols = HashSet::new();
+        let mut tape_alphabet = HashSet::new();
+        // symbols that are written over other symbols, these are not part of the input alphabet
+        let mut written = HashSet::new();
+        let mut final_states = HashSet::new();
+        let mut start_states = HashSet::new();
+        let mut declared = None;
+        let mut declared_tape = None;
+        for d in data {
+            match d {
+                AutomatonData::Edge(source, target, label) => {
+                    let values: Vec<_> = label.split(",").collect();
+                    let missing =
+                        |what: &str| ParseError(format!("No {} given in '{}'", what, label));
+                    let read = parse_char(values.first().ok_or(missing("Read Character"))?);
+                    let mut write = values
+                        .get(1)
+                        .map(|value| parse_char(value))
+                        .ok_or(missing("Written Character"))?;
+                    let movement = values
+                        .get(2)
+                        .and_then(|value| parse_move(value))
+                        .ok_or(missing("Movement (L, R or S)"))?;
+                    if read == ' ' {
+                        warn!("ignoring epsilon transition in an lba");
+                        continue;
+                    }
+                    // epsilon keeps the symbol under the head
+                    if write == ' ' {
+                        write = read;
+                    }
+                    let is_marker = |c| c == LEFT_MARKER || c == RIGHT_MARKER;
+                    if read != write && (is_marker(read) || is_marker(write)) {
+                        warn!(
+                            "end markers can't be written or overwritten, keeping '{}' in '{}'",
+                            read, label
+                        );
+                        write = read;
+                    }
+                    if read != write {
+                        written.insert(write);
+                    }
+                    for symbol in [read, write] {
+                        if !is_marker(symbol) {
+                            tape_alphabet.insert(symbol);
+                        }
+                    }
+                    if !is_marker(read) {
+                        read_symbols.insert(read);
+                    }
+                    states
+                        .entry(source)
+                        .or_insert(HashMap::new())
+                        .entry(read)
+                        .or_insert(Vec::new())
+                        .push((target, write, movement));
+                }
+                AutomatonData::Final(id) => {
+                    final_states.insert(id);
+                }
+                AutomatonData::Start(id) => {
+                    start_states.insert(id);
+                }
+                AutomatonData::Alphabet(symbols) => declared = Some(symbols),
+                AutomatonData::StackAlphabet(symbols) => declared_tape = Some(symbols),
+                AutomatonData::Output(..)
+                | AutomatonData::Threshold(_)
+                | AutomatonData::Name(..) => {}
+            }
+        }
+        logcheck_e(start_states.is_empty(), "No start state given")?;
+        logcheck_w(final_states.is_empty(), "No final states given");
+        // without a declaration, the input alphabet are all read symbols the lba doesn't write itself
+        let input: HashSet<char> = read_symbols.difference(&written).copied().collect();
+        let alphabet = declared_alphabet(declared, input, "alphabet");
+        let declared_tape = declared_tape.map(|symbols| [symbols, alphabet.clone()].concat());
+        declared_alphabet(declared_tape, tape_alphabet, "tape alphabet");
+        Ok(LBA {
+            states,
+            alphabet,
+            final_states: final_states.into_iter().collect(),
+            start_states: start_states.into_iter().collect(),
+        })
+    }
+}
+
+impl Automaton for LBA {
+    fn accepts_within(&self, word: &str, limit: &mut Limit) -> Result<bool, Exceeded> {
+        let tape: Vec<char> = once(LEFT_MARKER)
+            .chain(word.chars())
+            .chain(once(RIGHT_MARKER))
+            .collect();
+        // the tape can't grow, so there are only finitely many configurations
+        let mut seen: HashSet<Configuration> = HashSet::new();
+        let mut queue: VecDeque<Configuration> = VecDeque::new();
+        for state in self.start_states.iter() {
+            let start = (*state, 1, tape.clone());
+            if seen.insert(start.clone()) {
+                queue.push_back(start);
+            }
+        }
+        while let Some((state, position, tape)) = queue.pop_front() {
+            let transitions = self
+                .states
+                .get(&state)
+                .and_then(|s| s.get(&tape[position]))
+                .map(Vec::as_slice)
+                .unwrap_or(&[]);
+            if transitions.is_empty() && self.final_states.contains(&state) {
+                return Ok(true);
+            }
+            for &(next, write, movement) in transitions {
+                // runs that fall off the tape are discarded
+                let Some(next_position) = movement.apply(position, tape.len()) else {
+                    continue;
+                };
+                let mut next_tape = tape.clone();
+                next_tape[position] = write;
+                let configuration = (next, next_position, next_tape);
+                if !seen.contains(&configuration) {
+                    limit.step()?;
+                    seen.insert(configuration.clone());
+                    queue.push_back(configuration);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    fn view(&self) {
+        let mut out = String::new();
+        out.push_str("Type: LBA");
+        out.push_str(&format!(
+            "\nFinal States: {}",
+            format_states(&self.final_states)
+        ));
+        out.push_str(&format!(
+            "\nStart States: {}",
+            format_states(&self.start_states)
+        ));
+        let mut states: Vec<_> = self.states.iter().collect();
+        states.sort_by_key(|&(key, _)| key);
+        states.iter().for_each(|(id, map)| {
+            out.push_str(&format!("\nState {}:", id));
+            map.iter().for_each(|(read, transitions)| {
+                transitions.iter().for_each(|(target, write, movement)| {
+                    out.push_str(&format!(
+                        "\n    {},{},{} -> {}",
+                        read, write, movement, target
+                    ))
+                })
+            })
+        });
+        info!("{}", out);
+    }
+
+    fn states(&self) -> Vec<VertexId> {
+        sorted_states(
+            self.states
+                .iter()
+                .flat_map(|(id, map)| {
+                    once(*id).chain(map.values().flatten().map(|(target, _, _)| *target))
+                })
+                .chain(self.start_states.iter().copied())
+                .chain(self.final_states.iter().copied()),
+        )
+    }
+
+    fn transitions(&self) -> Vec<(VertexId, VertexId, String)> {
+        self.states
+            .iter()
+            .flat_map(|(id, map)| {
+                map.iter().flat_map(move |(read, transitions)| {
+                    transitions.iter().map(move |(target, write, movement)| {
+                        let label = format!(
+                            "{},{},{}",
+                            symbol_label(*read),
+                            symbol_label(*write),
+                            movement
+                        );
+                        (*id, *target, label)
+                    })
+                })
+            })
+            .collect()
+    }
+}
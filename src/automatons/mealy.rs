@@ -0,0 +1,177 @@
+use log::info;
+use log::warn;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::iter::once;
+
+use crate::shared::automaton::*;
+use crate::shared::budget::{Exceeded, Limit};
+use crate::shared::utils::declared_alphabet;
+use crate::shared::utils::logcheck_e;
+use crate::shared::utils::parse_output;
+use crate::shared::utils::parse_symbols;
+use crate::shared::utils::sorted_states;
+use crate::shared::utils::symbol_label;
+use crate::shared::utils::LabelSymbol;
+
+type Transition = (VertexId, String);
+
+// Transducer that writes an output on every transition
+pub struct Mealy {
+    states: HashMap<VertexId, HashMap<char, Transition>>,
+    alphabet: Vec<char>,
+    start_state: VertexId,
+}
+
+impl Mealy {
+    // None if the word can't be read completely
+    pub fn translate_within(
+        &self,
+        word: &str,
+        limit: &mut Limit,
+    ) -> Result<Option<String>, Exceeded> {
+        let mut current = &self.start_state;
+        let mut output = String::new();
+        for symbol in word.chars() {
+            limit.step()?;
+            if let Some((next, out)) = self.states.get(current).and_then(|s| s.get(&symbol)) {
+                current = next;
+                output.push_str(out);
+            } else {
+                return Ok(None);
+            }
+        }
+        Ok(Some(output))
+    }
+
+    pub fn new(data: Vec<AutomatonData>) -> Result<Mealy, ParseError> {
+        let mut states = HashMap::new();
+        let mut alphabet = HashSet::new();
+        let mut start_state = 0;
+        let mut declared = None;
+        let mut else_edges = Vec::new();
+        for d in data {
+            match d {
+                AutomatonData::Edge(source, target, label) => {
+                    let (input, output) = label.split_once('/').unwrap_or_else(|| {
+                        warn!(
+                            "mealy label '{}' has no output, using the empty word",
+                            label
+                        );
+                        (&label, "")
+                    });
+                    let output = parse_output(output);
+                    for symbol in parse_symbols(input)? {
+                        let label = match symbol {
+                            LabelSymbol::Symbol(c) => c,
+                            LabelSymbol::Epsilon => {
+                                warn!("epsilon transition in a mealy automaton");
+                                ' '
+                            }
+                            LabelSymbol::Else | LabelSymbol::Any => {
+                                else_edges.push((source, (target, output.clone())));
+                                continue;
+                            }
+                        };
+                        alphabet.insert(label);
+                        states
+                            .entry(source)
+                            .or_insert(HashMap::new())
+                            .insert(label, (target, output.clone()));
+                    }
+                }
+                AutomatonData::Final(_) => warn!("ignoring final state of a mealy automaton"),
+                AutomatonData::Start(id) => {
+                    if start_state != 0 {
+                        warn!("multiple start states in a mealy automaton, overwriting")
+                    };
+                    start_state = id;
+                }
+                AutomatonData::Alphabet(symbols) => declared = Some(symbols),
+                AutomatonData::StackAlphabet(_) => {
+                    warn!("ignoring stack alphabet of a mealy automaton")
+                }
+                AutomatonData::Output(..)
+                | AutomatonData::Threshold(_)
+                | AutomatonData::Name(..) => {}
+            }
+        }
+
+        logcheck_e(states.is_empty(), "No states given")?;
+        logcheck_e(start_state == 0, "No start state given")?;
+
+        let alphabet = declared_alphabet(declared, alphabet, "alphabet");
+        for (source, transition) in else_edges {
+            let map = states.entry(source).or_insert(HashMap::new());
+            for symbol in alphabet.iter() {
+                map.entry(*symbol).or_insert(transition.clone());
+            }
+        }
+
+        Ok(Mealy {
+            states,
+            alphabet,
+            start_state,
+        })
+    }
+}
+
+impl Automaton for Mealy {
+    fn accepts_within(&self, word: &str, limit: &mut Limit) -> Result<bool, Exceeded> {
+        self.translate_within(word, limit)
+            .map(|output| output.is_some())
+    }
+
+    fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    fn view(&self) {
+        let mut out = String::new();
+        out.push_str("Type: Mealy");
+        out.push_str(&format!("\nStart State: {}", &self.start_state));
+        let mut states: Vec<_> = self.states.iter().collect();
+        states.sort_by_key(|&(key, _)| key);
+        states.iter().for_each(|(id, map)| {
+            out.push_str(&format!("\nState {}:", id));
+            map.iter().for_each(|(label, (target, output))| {
+                out.push_str(&format!("\n    {}/{} -> {}", label, output, target))
+            })
+        });
+        info!("{}", out);
+    }
+
+    fn states(&self) -> Vec<VertexId> {
+        sorted_states(
+            self.states
+                .iter()
+                .flat_map(|(id, map)| once(*id).chain(map.values().map(|(target, _)| *target)))
+                .chain(once(self.start_state)),
+        )
+    }
+
+    fn transitions(&self) -> Vec<(VertexId, VertexId, String)> {
+        self.states
+            .iter()
+            .flat_map(|(id, map)| {
+                map.iter().map(|(symbol, (target, output))| {
+                    let output = if output.is_empty() { "ε" } else { output };
+                    (
+                        *id,
+                        *target,
+                        format!("{}/{}", symbol_label(*symbol), output),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    fn run(&self, word: &str, limit: &mut Limit) -> Result<Response, Exceeded> {
+        self.translate_within(word, limit).map(Response::Output)
+    }
+
+    fn is_transducer(&self) -> bool {
+        true
+    }
+}
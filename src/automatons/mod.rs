@@ -1,4 +1,11 @@
+pub mod buchi;
+pub mod counter;
 pub mod dfa;
 pub mod kpda;
+pub mod lba;
+pub mod mealy;
+pub mod moore;
 pub mod nfa;
 pub mod pda;
+pub mod twodfa;
+pub mod wfa;
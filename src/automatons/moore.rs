@@ -0,0 +1,185 @@
+use log::info;
+use log::warn;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::iter::once;
+
+use crate::shared::automaton::*;
+use crate::shared::budget::{Exceeded, Limit};
+use crate::shared::utils::declared_alphabet;
+use crate::shared::utils::logcheck_e;
+use crate::shared::utils::parse_output;
+use crate::shared::utils::parse_symbols;
+use crate::shared::utils::sorted_states;
+use crate::shared::utils::symbol_label;
+use crate::shared::utils::LabelSymbol;
+
+// Transducer that writes the output of every state it enters (including the start state)
+pub struct Moore {
+    states: HashMap<VertexId, HashMap<char, VertexId>>,
+    outputs: HashMap<VertexId, String>,
+    alphabet: Vec<char>,
+    start_state: VertexId,
+}
+
+impl Moore {
+    // None if the word can't be read completely
+    pub fn translate_within(
+        &self,
+        word: &str,
+        limit: &mut Limit,
+    ) -> Result<Option<String>, Exceeded> {
+        let mut current = &self.start_state;
+        let mut output = self.output(current).to_string();
+        for symbol in word.chars() {
+            limit.step()?;
+            if let Some(next) = self.states.get(current).and_then(|s| s.get(&symbol)) {
+                current = next;
+                output.push_str(self.output(current));
+            } else {
+                return Ok(None);
+            }
+        }
+        Ok(Some(output))
+    }
+
+    fn output(&self, state: &VertexId) -> &str {
+        self.outputs.get(state).map(String::as_str).unwrap_or("")
+    }
+
+    pub fn new(data: Vec<AutomatonData>) -> Result<Moore, ParseError> {
+        let mut states = HashMap::new();
+        let mut outputs = HashMap::new();
+        let mut alphabet = HashSet::new();
+        let mut start_state = 0;
+        let mut declared = None;
+        let mut else_edges = Vec::new();
+        let mut names = HashMap::new();
+        for d in data {
+            match d {
+                AutomatonData::Edge(source, target, label) => {
+                    for symbol in parse_symbols(&label)? {
+                        let label = match symbol {
+                            LabelSymbol::Symbol(c) => c,
+                            LabelSymbol::Epsilon => {
+                                warn!("epsilon transition in a moore automaton");
+                                ' '
+                            }
+                            LabelSymbol::Else | LabelSymbol::Any => {
+                                else_edges.push((source, target));
+                                continue;
+                            }
+                        };
+                        alphabet.insert(label);
+                        states
+                            .entry(source)
+                            .or_insert(HashMap::new())
+                            .insert(label, target);
+                    }
+                }
+                AutomatonData::Final(_) => warn!("ignoring final state of a moore automaton"),
+                AutomatonData::Start(id) => {
+                    if start_state != 0 {
+                        warn!("multiple start states in a moore automaton, overwriting")
+                    };
+                    start_state = id;
+                }
+                AutomatonData::Alphabet(symbols) => declared = Some(symbols),
+                AutomatonData::StackAlphabet(_) => {
+                    warn!("ignoring stack alphabet of a moore automaton")
+                }
+                AutomatonData::Output(id, output) => {
+                    outputs.insert(id, parse_output(&output));
+                }
+                AutomatonData::Name(id, name) => {
+                    names.insert(id, name);
+                }
+                AutomatonData::Threshold(_) => {}
+            }
+        }
+
+        logcheck_e(states.is_empty(), "No states given")?;
+        logcheck_e(start_state == 0, "No start state given")?;
+
+        let mut drawn: Vec<VertexId> = states
+            .iter()
+            .flat_map(|(source, map)| once(*source).chain(map.values().copied()))
+            .chain(once(start_state))
+            .collect();
+        drawn.sort();
+        drawn.dedup();
+        for state in drawn.iter().filter(|state| !outputs.contains_key(state)) {
+            let name = names.get(state).cloned().unwrap_or(state.to_string());
+            warn!("moore state {} has no output, using the empty word", name);
+        }
+
+        let alphabet = declared_alphabet(declared, alphabet, "alphabet");
+        for (source, target) in else_edges {
+            let map = states.entry(source).or_insert(HashMap::new());
+            for symbol in alphabet.iter() {
+                map.entry(*symbol).or_insert(target);
+            }
+        }
+
+        Ok(Moore {
+            states,
+            outputs,
+            alphabet,
+            start_state,
+        })
+    }
+}
+
+impl Automaton for Moore {
+    fn accepts_within(&self, word: &str, limit: &mut Limit) -> Result<bool, Exceeded> {
+        self.translate_within(word, limit)
+            .map(|output| output.is_some())
+    }
+
+    fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    fn view(&self) {
+        let mut out = String::new();
+        out.push_str("Type: Moore");
+        out.push_str(&format!("\nStart State: {}", &self.start_state));
+        let mut states: Vec<_> = self.states.iter().collect();
+        states.sort_by_key(|&(key, _)| key);
+        states.iter().for_each(|(id, map)| {
+            out.push_str(&format!("\nState {} / {}:", id, self.output(id)));
+            map.iter()
+                .for_each(|(label, target)| out.push_str(&format!("\n    {} -> {}", label, target)))
+        });
+        info!("{}", out);
+    }
+
+    fn states(&self) -> Vec<VertexId> {
+        sorted_states(
+            self.states
+                .iter()
+                .flat_map(|(id, map)| once(*id).chain(map.values().copied()))
+                .chain(self.outputs.keys().copied())
+                .chain(once(self.start_state)),
+        )
+    }
+
+    fn transitions(&self) -> Vec<(VertexId, VertexId, String)> {
+        self.states
+            .iter()
+            .flat_map(|(id, map)| {
+                map.iter()
+                    .map(|(symbol, target)| (*id, *target, symbol_label(*symbol)))
+            })
+            .collect()
+    }
+
+    fn run(&self, word: &str, limit: &mut Limit) -> Result<Response, Exceeded> {
+        self.translate_within(word, limit).map(Response::Output)
+    }
+
+    fn is_transducer(&self) -> bool {
+        true
+    }
+}
@@ -1,10 +1,14 @@
 use log::info;
+use log::warn;
 
 use crate::shared::automaton::*;
+use crate::shared::budget::{Exceeded, Limit};
+use crate::shared::regular::{RegularDfa, MAX_STATES};
 use crate::shared::utils::*;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::iter::once;
 
 #[allow(clippy::upper_case_acronyms)]
 pub struct NFA {
@@ -12,27 +16,39 @@ pub struct NFA {
     alphabet: Vec<char>,
     final_states: Vec<VertexId>,
     start_states: Vec<VertexId>,
+    // closures are only computed if there are epsilon transitions
+    has_epsilon: bool,
 }
 
 impl NFA {
-    pub fn accepts(&self, word: &str) -> bool {
-        let mut currents: VecDeque<_> = self.start_states.iter().collect();
-        for symbol in word.chars() {
-            for _ in 0..currents.len() {
-                let current = currents.pop_front().unwrap();
-                if let Some(next) = self.states.get(current).and_then(|s| s.get(&symbol)) {
-                    for s in next.iter() {
-                        currents.push_back(s);
-                    }
+    pub fn has_epsilon(&self) -> bool {
+        self.has_epsilon
+    }
+
+    // All states reachable with epsilon transitions (including the given ones), sorted
+    pub fn closure(&self, states: impl IntoIterator<Item = VertexId>) -> Vec<VertexId> {
+        let mut closure: Vec<VertexId> = states.into_iter().collect();
+        closure.sort();
+        closure.dedup();
+        if !self.has_epsilon {
+            return closure;
+        }
+        let mut queue: VecDeque<VertexId> = closure.iter().copied().collect();
+        while let Some(state) = queue.pop_front() {
+            for next in self.successors(state, ' ') {
+                if let Err(i) = closure.binary_search(next) {
+                    closure.insert(i, *next);
+                    queue.push_back(*next);
                 }
             }
         }
-        self.final_states.iter().any(|f| currents.contains(&f))
+        closure
     }
 
-    pub fn view(&self) {
+    // The text shown by view, also used by automatons built on top of an nfa
+    pub fn describe(&self, automaton_type: &str) -> String {
         let mut out = String::new();
-        out.push_str("Type: NFA");
+        out.push_str(&format!("Type: {}", automaton_type));
         out.push_str(&format!(
             "\nFinal States: {}",
             format_states(&self.final_states)
@@ -53,41 +69,192 @@ impl NFA {
                 ))
             })
         });
-        info!("{}", out);
+        out
     }
-    pub fn new(data: Vec<AutomatonData>) -> NFA {
+    pub fn new(data: Vec<AutomatonData>) -> Result<NFA, ParseError> {
         let mut states = HashMap::new();
         let mut alphabet = HashSet::new();
         let mut final_states = HashSet::new();
         let mut start_states = HashSet::new();
-        data.into_iter().for_each(|d| match d {
-            AutomatonData::Edge(source, target, label) => {
-                let label = parse_char(&label);
-                alphabet.insert(label);
-                states
-                    .entry(source)
-                    .or_insert(HashMap::new())
-                    .entry(label)
-                    .or_insert(Vec::new())
-                    .push(target);
+        let mut declared = None;
+        let mut else_edges = Vec::new();
+        let mut any_edges = Vec::new();
+        for d in data {
+            match d {
+                AutomatonData::Edge(source, target, label) => {
+                    for symbol in parse_symbols(&label)? {
+                        let label = match symbol {
+                            LabelSymbol::Symbol(c) => c,
+                            LabelSymbol::Epsilon => ' ',
+                            LabelSymbol::Else => {
+                                else_edges.push((source, target));
+                                continue;
+                            }
+                            LabelSymbol::Any => {
+                                any_edges.push((source, target));
+                                continue;
+                            }
+                        };
+                        alphabet.insert(label);
+                        states
+                            .entry(source)
+                            .or_insert(HashMap::new())
+                            .entry(label)
+                            .or_insert(Vec::new())
+                            .push(target);
+                    }
+                }
+                AutomatonData::Final(id) => {
+                    final_states.insert(id);
+                }
+                AutomatonData::Start(id) => {
+                    start_states.insert(id);
+                }
+                AutomatonData::Alphabet(symbols) => declared = Some(symbols),
+                AutomatonData::StackAlphabet(_) => warn!("ignoring stack alphabet of an nfa"),
+                AutomatonData::Output(..)
+                | AutomatonData::Threshold(_)
+                | AutomatonData::Name(..) => {}
             }
-            AutomatonData::Final(id) => {
-                final_states.insert(id);
+        }
+        logcheck_e(start_states.is_empty(), "No start state given")?;
+        let alphabet = declared_alphabet(declared, alphabet, "alphabet");
+        // else only applies to symbols without an explicit transition from the state
+        let explicit: HashMap<VertexId, Vec<char>> = states
+            .iter()
+            .map(|(id, map): (&VertexId, &HashMap<char, Vec<VertexId>>)| {
+                (*id, map.keys().copied().collect())
+            })
+            .collect();
+        for (source, target) in else_edges {
+            let map = states.entry(source).or_insert(HashMap::new());
+            for symbol in alphabet.iter() {
+                if !explicit.get(&source).is_some_and(|e| e.contains(symbol)) {
+                    map.entry(*symbol).or_insert(Vec::new()).push(target);
+                }
             }
-            AutomatonData::Start(id) => {
-                start_states.insert(id);
+        }
+        for (source, target) in any_edges {
+            let map = states.entry(source).or_insert(HashMap::new());
+            for symbol in alphabet.iter() {
+                map.entry(*symbol).or_insert(Vec::new()).push(target);
             }
-        });
-        assert!(!start_states.is_empty(), "No start state given");
-        NFA {
+        }
+        Ok(NFA {
+            has_epsilon: states.values().any(|map| map.contains_key(&' ')),
             states,
-            alphabet: alphabet.into_iter().collect(),
+            alphabet,
             final_states: final_states.into_iter().collect(),
             start_states: start_states.into_iter().collect(),
+        })
+    }
+
+    pub fn start_states(&self) -> &[VertexId] {
+        &self.start_states
+    }
+
+    pub fn final_states(&self) -> &[VertexId] {
+        &self.final_states
+    }
+
+    pub fn successors(&self, state: VertexId, symbol: char) -> &[VertexId] {
+        self.states
+            .get(&state)
+            .and_then(|s| s.get(&symbol))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    // All states reachable in one step, regardless of the symbol
+    pub fn neighbours(&self, state: VertexId) -> impl Iterator<Item = (char, VertexId)> + '_ {
+        self.states
+            .get(&state)
+            .into_iter()
+            .flat_map(|s| s.iter())
+            .flat_map(|(symbol, targets)| targets.iter().map(|target| (*symbol, *target)))
+    }
+}
+
+impl Automaton for NFA {
+    fn accepts_within(&self, word: &str, limit: &mut Limit) -> Result<bool, Exceeded> {
+        let mut currents = self.closure(self.start_states.iter().copied());
+        for symbol in word.chars() {
+            let mut next = Vec::new();
+            for current in currents {
+                for s in self.successors(current, symbol) {
+                    limit.step()?;
+                    next.push(*s);
+                }
+            }
+            currents = self.closure(next);
         }
+        Ok(self.final_states.iter().any(|f| currents.contains(f)))
     }
 
-    pub fn alphabet(&self) -> &Vec<char> {
+    // the set of current states after every symbol
+    fn trace(&self, word: &str) -> Option<Vec<String>> {
+        let set = |states: &[VertexId]| match states {
+            [] => "∅".to_string(),
+            states => format!("{{{}}}", format_states(states)),
+        };
+        let mut currents = self.closure(self.start_states.iter().copied());
+        let mut steps = vec![format!("start in {}", set(&currents))];
+        for symbol in word.chars() {
+            let next = self.closure(
+                currents
+                    .iter()
+                    .flat_map(|current| self.successors(*current, symbol))
+                    .copied(),
+            );
+            steps.push(format!("{} -{}-> {}", set(&currents), symbol, set(&next)));
+            currents = next;
+        }
+        Some(steps)
+    }
+
+    fn alphabet(&self) -> &[char] {
         &self.alphabet
     }
+
+    fn view(&self) {
+        info!("{}", self.describe("NFA"));
+    }
+
+    // Subset construction
+    fn to_regular(&self, alphabet: &[char]) -> Option<RegularDfa> {
+        RegularDfa::explore(
+            alphabet,
+            self.closure(self.start_states.iter().copied()),
+            |states, symbol| {
+                self.closure(
+                    states
+                        .iter()
+                        .flat_map(|state| self.successors(*state, symbol))
+                        .copied(),
+                )
+            },
+            |states| states.iter().any(|state| self.final_states.contains(state)),
+            MAX_STATES,
+        )
+    }
+
+    fn states(&self) -> Vec<VertexId> {
+        sorted_states(
+            self.states
+                .keys()
+                .flat_map(|id| once(*id).chain(self.neighbours(*id).map(|(_, target)| target)))
+                .chain(self.start_states.iter().copied())
+                .chain(self.final_states.iter().copied()),
+        )
+    }
+
+    fn transitions(&self) -> Vec<(VertexId, VertexId, String)> {
+        self.states
+            .keys()
+            .flat_map(|id| {
+                self.neighbours(*id)
+                    .map(|(symbol, target)| (*id, target, symbol_label(symbol)))
+            })
+            .collect()
+    }
 }
@@ -1,8 +1,13 @@
 use log::info;
 
 use crate::shared::automaton::*;
+use crate::shared::budget::{Exceeded, Limit};
+use crate::shared::utils::declared_alphabet;
 use crate::shared::utils::format_states;
+use crate::shared::utils::logcheck_e;
 use crate::shared::utils::parse_char;
+use crate::shared::utils::sorted_states;
+use crate::shared::utils::symbol_label;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::iter::once;
@@ -11,6 +16,10 @@ type Symbol = char;
 type StackChar = char;
 type Destinations = Vec<(VertexId, String)>;
 type Transitions = HashMap<(Symbol, StackChar), Destinations>;
+// Nonterminal of the grammar from the triple construction: (p, X, Some(q)) derives the words
+// that lead from p to q and remove X from the stack, (p, X, None) the words that lead from p
+// to a final state while X stays on the stack
+type Variable = (VertexId, StackChar, Option<VertexId>);
 
 #[allow(clippy::upper_case_acronyms)]
 pub struct PDA {
@@ -21,7 +30,139 @@ pub struct PDA {
 }
 
 impl PDA {
-    pub fn accepts(&self, word: &str) -> bool {
+    pub fn new(data: Vec<AutomatonData>) -> Result<PDA, ParseError> {
+        let mut states = HashMap::new();
+        let mut alphabet = HashSet::new();
+        let mut final_states = HashSet::new();
+        let mut start_states = HashSet::new();
+        let mut stack_alphabet = HashSet::from(['#']);
+        let mut declared = None;
+        let mut declared_stack = None;
+        for d in data {
+            match d {
+                AutomatonData::Edge(source, target, label) => {
+                    let values: Vec<_> = label.split(",").collect();
+                    let missing =
+                        |what: &str| ParseError(format!("No {} given in '{}'", what, label));
+                    let label = parse_char(values.first().ok_or(missing("Character"))?);
+                    let current_stack =
+                        parse_char(values.get(1).ok_or(missing("Current Stackvalue"))?);
+                    let next_stack = values.get(2).ok_or(missing("Next Stackvalue"))?;
+                    alphabet.insert(label);
+                    stack_alphabet.insert(current_stack);
+                    stack_alphabet.extend(next_stack.chars());
+                    states
+                        .entry(source)
+                        .or_insert(HashMap::new())
+                        .entry((label, current_stack))
+                        .or_insert(Vec::new())
+                        .push((target, next_stack.to_string()));
+                }
+                AutomatonData::Final(id) => {
+                    final_states.insert(id);
+                }
+                AutomatonData::Start(id) => {
+                    start_states.insert(id);
+                }
+                AutomatonData::Alphabet(symbols) => declared = Some(symbols),
+                AutomatonData::StackAlphabet(symbols) => declared_stack = Some(symbols),
+                AutomatonData::Output(..)
+                | AutomatonData::Threshold(_)
+                | AutomatonData::Name(..) => {}
+            }
+        }
+        logcheck_e(start_states.is_empty(), "No start state given")?;
+        // the initial stack symbol is always part of the stack alphabet
+        let declared_stack = declared_stack.map(|symbols| [symbols, vec!['#']].concat());
+        declared_alphabet(declared_stack, stack_alphabet, "stack alphabet");
+        Ok(PDA {
+            states,
+            alphabet: declared_alphabet(declared, alphabet, "alphabet"),
+            final_states: final_states.into_iter().collect(),
+            start_states: start_states.into_iter().collect(),
+        })
+    }
+
+    // The shortest accepted word (the first in alphabetical order), None if the language is empty
+    // computed as the shortest word every variable of the equivalent grammar derives
+    fn shortest_accepted(&self) -> Option<String> {
+        let mut all_states: Vec<VertexId> = self
+            .states
+            .iter()
+            .flat_map(|(id, map)| once(*id).chain(map.values().flatten().map(|(t, _)| *t)))
+            .chain(self.start_states.iter().copied())
+            .chain(self.final_states.iter().copied())
+            .collect();
+        all_states.sort();
+        all_states.dedup();
+        let stack_symbols: HashSet<StackChar> = self
+            .states
+            .values()
+            .flat_map(|map| {
+                map.iter().flat_map(|((_, top), destinations)| {
+                    once(*top).chain(destinations.iter().flat_map(|(_, push)| push.chars()))
+                })
+            })
+            .chain(once('#'))
+            .collect();
+
+        let mut best: HashMap<Variable, String> = HashMap::new();
+        // a final state is reached without reading anything
+        for state in self.final_states.iter() {
+            for symbol in stack_symbols.iter() {
+                best.insert((*state, *symbol, None), String::new());
+            }
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (state, transitions) in self.states.iter() {
+                for ((symbol, top), destinations) in transitions.iter() {
+                    let read: String = once(*symbol).filter(|c| *c != ' ').collect();
+                    for (target, push) in destinations {
+                        let mut candidates = Vec::new();
+                        // the words leading to every state after removing the pushed symbols from the top
+                        let mut reached = HashMap::from([(*target, read.clone())]);
+                        for pushed in push.chars().rev() {
+                            let mut next = HashMap::new();
+                            for (from, word) in reached.iter() {
+                                // the pushed symbol stays until a final state is reached
+                                if let Some(rest) = best.get(&(*from, pushed, None)) {
+                                    candidates.push((None, format!("{word}{rest}")));
+                                }
+                                for to in all_states.iter() {
+                                    if let Some(rest) = best.get(&(*from, pushed, Some(*to))) {
+                                        improve(&mut next, *to, format!("{word}{rest}"));
+                                    }
+                                }
+                            }
+                            reached = next;
+                        }
+                        candidates.extend(reached.into_iter().map(|(to, word)| (Some(to), word)));
+                        for (to, word) in candidates {
+                            changed |= improve(&mut best, (*state, *top, to), word);
+                        }
+                    }
+                }
+            }
+        }
+
+        // without final states, the word has to remove the initial stack symbol
+        let ends: Vec<Option<VertexId>> = if self.final_states.is_empty() {
+            all_states.iter().copied().map(Some).collect()
+        } else {
+            vec![None]
+        };
+        self.start_states
+            .iter()
+            .flat_map(|start| ends.iter().filter_map(|end| best.get(&(*start, '#', *end))))
+            .min_by_key(|word| (word.chars().count(), *word))
+            .cloned()
+    }
+}
+
+impl Automaton for PDA {
+    fn accepts_within(&self, word: &str, limit: &mut Limit) -> Result<bool, Exceeded> {
         let mut currents: Vec<(VertexId, String)> = self
             .start_states
             .clone()
@@ -50,6 +191,7 @@ impl PDA {
                         let mut next = next;
                         next.1 = stack.clone() + &next.1;
                         if !seen_states.contains(&next) {
+                            limit.step()?;
                             seen_states.push(next.clone());
                             currents.push(next);
                         }
@@ -61,6 +203,7 @@ impl PDA {
                         let mut next = next;
                         next.1 = stack.clone() + &next.1;
                         if !new.contains(&next) {
+                            limit.step()?;
                             new.push(next);
                         }
                     }
@@ -69,16 +212,20 @@ impl PDA {
             currents = new;
         }
 
-        if self.final_states.is_empty() {
+        Ok(if self.final_states.is_empty() {
             currents.iter().any(|(_, stack)| stack.is_empty())
         } else {
             currents
                 .iter()
                 .any(|(state, _)| self.final_states.contains(state))
-        }
+        })
     }
 
-    pub fn view(&self) {
+    fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    fn view(&self) {
         let mut out = String::new();
         out.push_str("Type: PDA");
         out.push_str(&format!(
@@ -104,43 +251,47 @@ impl PDA {
         });
         info!("{}", out);
     }
-    pub fn new(data: Vec<AutomatonData>) -> PDA {
-        let mut states = HashMap::new();
-        let mut alphabet = HashSet::new();
-        let mut final_states = HashSet::new();
-        let mut start_states = HashSet::new();
-        data.into_iter().for_each(|d| match d {
-            AutomatonData::Edge(source, target, label) => {
-                let values: Vec<_> = label.split(",").collect();
-                let label = parse_char(values.first().expect("No Character given"));
-                let current_stack = parse_char(values.get(1).expect("No Current Stackvalue given"));
-                let next_stack = values.get(2).expect("No Next Stackvalue given");
-                alphabet.insert(label);
-                states
-                    .entry(source)
-                    .or_insert(HashMap::new())
-                    .entry((label, current_stack))
-                    .or_insert(Vec::new())
-                    .push((target, next_stack.to_string()));
-            }
-            AutomatonData::Final(id) => {
-                final_states.insert(id);
-            }
-            AutomatonData::Start(id) => {
-                start_states.insert(id);
-            }
-        });
-        assert!(!start_states.is_empty(), "No start state given");
-        PDA {
-            states,
-            alphabet: alphabet.into_iter().collect(),
-            final_states: final_states.into_iter().collect(),
-            start_states: start_states.into_iter().collect(),
-        }
+
+    fn shortest_word(&self) -> Option<Option<String>> {
+        Some(self.shortest_accepted())
     }
 
-    pub fn alphabet(&self) -> &Vec<char> {
-        &self.alphabet
+    fn states(&self) -> Vec<VertexId> {
+        sorted_states(
+            self.states
+                .iter()
+                .flat_map(|(id, map)| {
+                    once(*id).chain(map.values().flatten().map(|(target, _)| *target))
+                })
+                .chain(self.start_states.iter().copied())
+                .chain(self.final_states.iter().copied()),
+        )
+    }
+
+    fn transitions(&self) -> Vec<(VertexId, VertexId, String)> {
+        self.states
+            .iter()
+            .flat_map(|(id, map)| {
+                map.iter().flat_map(move |((symbol, top), destinations)| {
+                    destinations.iter().map(move |(target, push)| {
+                        let label =
+                            format!("{},{},{}", symbol_label(*symbol), symbol_label(*top), push);
+                        (*id, *target, label)
+                    })
+                })
+            })
+            .collect()
+    }
+}
+
+// Keeps the shorter word (or the first in alphabetical order) for a key, true if it changed
+fn improve<K: Eq + std::hash::Hash>(words: &mut HashMap<K, String>, key: K, word: String) -> bool {
+    match words.get(&key) {
+        Some(old) if (old.chars().count(), old) <= (word.chars().count(), &word) => false,
+        _ => {
+            words.insert(key, word);
+            true
+        }
     }
 }
 
@@ -0,0 +1,267 @@
+use log::info;
+use log::warn;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::iter::once;
+
+use crate::shared::automaton::*;
+use crate::shared::budget::{Exceeded, Limit};
+use crate::shared::regular::{RegularDfa, MAX_STATES};
+use crate::shared::utils::*;
+
+type Transition = (VertexId, Move);
+
+// Two-way dfa: reads the input between the end markers < and > and can move its head in both directions
+// The head starts on the first symbol, the word is accepted if the automaton halts in a final state
+pub struct TwoDFA {
+    states: HashMap<VertexId, HashMap<char, Transition>>,
+    alphabet: Vec<char>,
+    final_states: Vec<VertexId>,
+    start_state: VertexId,
+}
+
+// What happens after the head enters a cell in some state
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Outcome {
+    // the head leaves the cell to the right in this state
+    Exit(VertexId),
+    Accept,
+    Reject,
+}
+
+impl TwoDFA {
+    fn transition(&self, state: VertexId, symbol: char) -> Option<&Transition> {
+        self.states.get(&state).and_then(|s| s.get(&symbol))
+    }
+
+    // Runs the automaton on a single cell, entered in the given state,
+    // returns_from tells what happens after the head moves left out of the cell
+    fn cell_outcome(
+        &self,
+        state: VertexId,
+        symbol: char,
+        returns_from: impl Fn(VertexId) -> Outcome,
+    ) -> Outcome {
+        let mut state = state;
+        let mut seen = HashSet::new();
+        while seen.insert(state) {
+            let Some(&(next, movement)) = self.transition(state, symbol) else {
+                return if self.final_states.contains(&state) {
+                    Outcome::Accept
+                } else {
+                    Outcome::Reject
+                };
+            };
+            match movement {
+                Move::Right => return Outcome::Exit(next),
+                Move::Stay => state = next,
+                Move::Left => match returns_from(next) {
+                    Outcome::Exit(back) => state = back,
+                    outcome => return outcome,
+                },
+            }
+        }
+        Outcome::Reject
+    }
+
+    pub fn new(data: Vec<AutomatonData>) -> Result<TwoDFA, ParseError> {
+        let mut states = HashMap::new();
+        let mut alphabet = HashSet::new();
+        let mut final_states = HashSet::new();
+        let mut start_state = 0;
+        let mut declared = None;
+        let mut else_edges = Vec::new();
+        for d in data {
+            match d {
+                AutomatonData::Edge(source, target, label) => {
+                    let (symbols, movement) = match label
+                        .rsplit_once(',')
+                        .and_then(|(symbols, movement)| Some((symbols, parse_move(movement)?)))
+                    {
+                        Some(split) => split,
+                        None => {
+                            warn!("2dfa label '{}' has no direction, moving right", label);
+                            (label.as_str(), Move::Right)
+                        }
+                    };
+                    for symbol in parse_symbols(symbols)? {
+                        let symbol = match symbol {
+                            LabelSymbol::Symbol(c) => c,
+                            LabelSymbol::Epsilon => {
+                                warn!("ignoring epsilon transition in a 2dfa");
+                                continue;
+                            }
+                            LabelSymbol::Else | LabelSymbol::Any => {
+                                else_edges.push((source, (target, movement)));
+                                continue;
+                            }
+                        };
+                        if symbol != LEFT_MARKER && symbol != RIGHT_MARKER {
+                            alphabet.insert(symbol);
+                        }
+                        states
+                            .entry(source)
+                            .or_insert(HashMap::new())
+                            .insert(symbol, (target, movement));
+                    }
+                }
+                AutomatonData::Final(id) => {
+                    final_states.insert(id);
+                }
+                AutomatonData::Start(id) => {
+                    if start_state != 0 {
+                        warn!("multiple start states in a 2dfa, overwriting")
+                    };
+                    start_state = id;
+                }
+                AutomatonData::Alphabet(symbols) => declared = Some(symbols),
+                AutomatonData::StackAlphabet(_) => warn!("ignoring stack alphabet of a 2dfa"),
+                AutomatonData::Output(..)
+                | AutomatonData::Threshold(_)
+                | AutomatonData::Name(..) => {}
+            }
+        }
+
+        logcheck_e(states.is_empty(), "No states given")?;
+        logcheck_w(final_states.is_empty(), "No final states given");
+        logcheck_e(start_state == 0, "No start state given")?;
+
+        let alphabet = declared_alphabet(declared, alphabet, "alphabet");
+        // else never matches the end markers
+        for (source, transition) in else_edges {
+            let map = states.entry(source).or_insert(HashMap::new());
+            for symbol in alphabet.iter() {
+                map.entry(*symbol).or_insert(transition);
+            }
+        }
+
+        Ok(TwoDFA {
+            states,
+            alphabet,
+            final_states: final_states.into_iter().collect(),
+            start_state,
+        })
+    }
+}
+
+impl Automaton for TwoDFA {
+    fn accepts_within(&self, word: &str, limit: &mut Limit) -> Result<bool, Exceeded> {
+        let tape: Vec<char> = once(LEFT_MARKER)
+            .chain(word.chars())
+            .chain(once(RIGHT_MARKER))
+            .collect();
+        let mut state = self.start_state;
+        let mut position = 1;
+        // there are only finitely many configurations, a repeated one means the automaton loops forever
+        let mut seen = HashSet::new();
+        while seen.insert((state, position)) {
+            limit.step()?;
+            let Some(&(next, movement)) = self.transition(state, tape[position]) else {
+                return Ok(self.final_states.contains(&state));
+            };
+            match movement.apply(position, tape.len()) {
+                Some(next_position) => {
+                    state = next;
+                    position = next_position;
+                }
+                // the head fell off the tape
+                None => return Ok(false),
+            }
+        }
+        Ok(false)
+    }
+
+    fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    fn view(&self) {
+        let mut out = String::new();
+        out.push_str("Type: 2DFA");
+        out.push_str(&format!(
+            "\nFinal States: {}",
+            format_states(&self.final_states)
+        ));
+        out.push_str(&format!("\nStart State: {}", &self.start_state));
+        let mut states: Vec<_> = self.states.iter().collect();
+        states.sort_by_key(|&(key, _)| key);
+        states.iter().for_each(|(id, map)| {
+            out.push_str(&format!("\nState {}:", id));
+            map.iter().for_each(|(label, (target, movement))| {
+                out.push_str(&format!("\n    {},{} -> {}", label, movement, target))
+            })
+        });
+        info!("{}", out);
+    }
+
+    // Shepherdson's construction: after a prefix, the dfa remembers what happens when the
+    // head first leaves the prefix to the right, and for every state in which the head
+    // could come back into the prefix, in which state it leaves it again
+    fn to_regular(&self, alphabet: &[char]) -> Option<RegularDfa> {
+        let mut all_states: Vec<VertexId> = self
+            .states
+            .iter()
+            .flat_map(|(id, map)| once(*id).chain(map.values().map(|(target, _)| *target)))
+            .chain(once(self.start_state))
+            .collect();
+        all_states.sort();
+        all_states.dedup();
+        let index = |state: VertexId| all_states.binary_search(&state).unwrap();
+        // the cells of a prefix, given what happens when the head moves left out of the new cell
+        let extend = |table: &Vec<Outcome>, symbol: char| -> Vec<Outcome> {
+            all_states
+                .iter()
+                .map(|&state| self.cell_outcome(state, symbol, |back| table[index(back)]))
+                .collect()
+        };
+        // the left marker can't be left to the left
+        let left: Vec<Outcome> = extend(&vec![Outcome::Reject; all_states.len()], LEFT_MARKER);
+        RegularDfa::explore(
+            alphabet,
+            (Outcome::Exit(self.start_state), left),
+            |(first, table), symbol| {
+                let next = extend(table, symbol);
+                let first = match first {
+                    Outcome::Exit(state) => next[index(*state)],
+                    outcome => *outcome,
+                };
+                (first, next)
+            },
+            |(first, table)| match first {
+                // leaving the right marker to the right falls off the tape
+                Outcome::Exit(state) => {
+                    self.cell_outcome(*state, RIGHT_MARKER, |back| table[index(back)])
+                        == Outcome::Accept
+                }
+                outcome => *outcome == Outcome::Accept,
+            },
+            MAX_STATES,
+        )
+    }
+
+    fn states(&self) -> Vec<VertexId> {
+        sorted_states(
+            self.states
+                .iter()
+                .flat_map(|(id, map)| once(*id).chain(map.values().map(|(target, _)| *target)))
+                .chain(once(self.start_state))
+                .chain(self.final_states.iter().copied()),
+        )
+    }
+
+    fn transitions(&self) -> Vec<(VertexId, VertexId, String)> {
+        self.states
+            .iter()
+            .flat_map(|(id, map)| {
+                map.iter().map(|(symbol, (target, movement))| {
+                    (
+                        *id,
+                        *target,
+                        format!("{},{}", symbol_label(*symbol), movement),
+                    )
+                })
+            })
+            .collect()
+    }
+}
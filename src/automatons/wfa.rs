@@ -0,0 +1,216 @@
+use log::info;
+use log::warn;
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::iter::once;
+
+use crate::shared::automaton::*;
+use crate::shared::budget::{Exceeded, Limit};
+use crate::shared::utils::*;
+
+type Destinations = Vec<(VertexId, f64)>;
+
+// Weighted automaton over the real numbers: like an nfa whose transitions carry weights
+// The weight of a word is the sum over all runs of the product of their weights,
+// start and final states have weight 1 and a word is accepted if its weight is above the threshold
+pub struct WFA {
+    states: HashMap<VertexId, HashMap<char, Destinations>>,
+    alphabet: Vec<char>,
+    final_states: Vec<VertexId>,
+    start_states: Vec<VertexId>,
+    threshold: f64,
+}
+
+impl WFA {
+    // The start vector multiplied with the transition matrix of every symbol and the final vector
+    fn total_weight(&self, word: &str, limit: &mut Limit) -> Result<f64, Exceeded> {
+        // sorted, so the floating point sums don't depend on the hash order
+        let mut currents: BTreeMap<VertexId, f64> = self
+            .start_states
+            .iter()
+            .map(|state| (*state, 1.0))
+            .collect();
+        for symbol in word.chars() {
+            let mut next = BTreeMap::new();
+            for (state, weight) in currents {
+                let destinations = self.states.get(&state).and_then(|s| s.get(&symbol));
+                for (target, factor) in destinations.into_iter().flatten() {
+                    limit.step()?;
+                    *next.entry(*target).or_insert(0.0) += weight * factor;
+                }
+            }
+            currents = next;
+        }
+        Ok(currents
+            .iter()
+            .filter(|(state, _)| self.final_states.contains(state))
+            .map(|(_, weight)| weight)
+            .sum())
+    }
+
+    pub fn new(data: Vec<AutomatonData>) -> Result<WFA, ParseError> {
+        let mut states = HashMap::new();
+        let mut alphabet = HashSet::new();
+        let mut final_states = HashSet::new();
+        let mut start_states = HashSet::new();
+        let mut declared = None;
+        let mut threshold = 0.0;
+        let mut else_edges = Vec::new();
+        let mut any_edges = Vec::new();
+        for d in data {
+            match d {
+                AutomatonData::Edge(source, target, label) => {
+                    let (symbols, weight) = match label.rsplit_once(':') {
+                        Some((symbols, weight)) => (
+                            symbols,
+                            parse_weight(weight)
+                                .ok_or(ParseError(format!("'{}' is not a weight", weight)))?,
+                        ),
+                        None => {
+                            warn!("wfa label '{}' has no weight, using 1", label);
+                            (label.as_str(), 1.0)
+                        }
+                    };
+                    for symbol in parse_symbols(symbols)? {
+                        let symbol = match symbol {
+                            LabelSymbol::Symbol(c) => c,
+                            LabelSymbol::Epsilon => {
+                                warn!("ignoring epsilon transition in a wfa");
+                                continue;
+                            }
+                            LabelSymbol::Else => {
+                                else_edges.push((source, (target, weight)));
+                                continue;
+                            }
+                            LabelSymbol::Any => {
+                                any_edges.push((source, (target, weight)));
+                                continue;
+                            }
+                        };
+                        alphabet.insert(symbol);
+                        states
+                            .entry(source)
+                            .or_insert(HashMap::new())
+                            .entry(symbol)
+                            .or_insert(Vec::new())
+                            .push((target, weight));
+                    }
+                }
+                AutomatonData::Final(id) => {
+                    final_states.insert(id);
+                }
+                AutomatonData::Start(id) => {
+                    start_states.insert(id);
+                }
+                AutomatonData::Alphabet(symbols) => declared = Some(symbols),
+                AutomatonData::StackAlphabet(_) => warn!("ignoring stack alphabet of a wfa"),
+                AutomatonData::Output(..) | AutomatonData::Name(..) => {}
+                AutomatonData::Threshold(value) => threshold = value,
+            }
+        }
+        logcheck_e(start_states.is_empty(), "No start state given")?;
+        let alphabet = declared_alphabet(declared, alphabet, "alphabet");
+        // else only applies to symbols without an explicit transition from the state
+        let explicit: HashMap<VertexId, Vec<char>> = states
+            .iter()
+            .map(|(id, map): (&VertexId, &HashMap<char, Destinations>)| {
+                (*id, map.keys().copied().collect())
+            })
+            .collect();
+        for (source, destination) in else_edges {
+            let map = states.entry(source).or_insert(HashMap::new());
+            for symbol in alphabet.iter() {
+                if !explicit.get(&source).is_some_and(|e| e.contains(symbol)) {
+                    map.entry(*symbol).or_insert(Vec::new()).push(destination);
+                }
+            }
+        }
+        for (source, destination) in any_edges {
+            let map = states.entry(source).or_insert(HashMap::new());
+            for symbol in alphabet.iter() {
+                map.entry(*symbol).or_insert(Vec::new()).push(destination);
+            }
+        }
+        Ok(WFA {
+            states,
+            alphabet,
+            final_states: final_states.into_iter().collect(),
+            start_states: start_states.into_iter().collect(),
+            threshold,
+        })
+    }
+}
+
+impl Automaton for WFA {
+    fn accepts_within(&self, word: &str, limit: &mut Limit) -> Result<bool, Exceeded> {
+        Ok(self.total_weight(word, limit)? > self.threshold)
+    }
+
+    fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    fn view(&self) {
+        let mut out = String::new();
+        out.push_str("Type: WFA");
+        out.push_str(&format!("\nThreshold: {}", self.threshold));
+        out.push_str(&format!(
+            "\nFinal States: {}",
+            format_states(&self.final_states)
+        ));
+        out.push_str(&format!(
+            "\nStart States: {}",
+            format_states(&self.start_states)
+        ));
+        let mut states: Vec<_> = self.states.iter().collect();
+        states.sort_by_key(|&(key, _)| key);
+        states.iter().for_each(|(id, map)| {
+            out.push_str(&format!("\nState {}:", id));
+            map.iter().for_each(|(label, destinations)| {
+                destinations.iter().for_each(|(target, weight)| {
+                    out.push_str(&format!("\n    {}:{} -> {}", label, weight, target))
+                })
+            })
+        });
+        info!("{}", out);
+    }
+
+    fn states(&self) -> Vec<VertexId> {
+        sorted_states(
+            self.states
+                .iter()
+                .flat_map(|(id, map)| {
+                    once(*id).chain(map.values().flatten().map(|(target, _)| *target))
+                })
+                .chain(self.start_states.iter().copied())
+                .chain(self.final_states.iter().copied()),
+        )
+    }
+
+    fn transitions(&self) -> Vec<(VertexId, VertexId, String)> {
+        self.states
+            .iter()
+            .flat_map(|(id, map)| {
+                map.iter().flat_map(move |(symbol, destinations)| {
+                    destinations.iter().map(move |(target, weight)| {
+                        (
+                            *id,
+                            *target,
+                            format!("{}:{}", symbol_label(*symbol), weight),
+                        )
+                    })
+                })
+            })
+            .collect()
+    }
+
+    fn is_weighted(&self) -> bool {
+        true
+    }
+
+    fn weight_within(&self, word: &str, limit: &mut Limit) -> Result<Option<f64>, Exceeded> {
+        self.total_weight(word, limit).map(Some)
+    }
+}
@@ -0,0 +1,16 @@
+//! Parsing, simulation and comparison of automatons drawn in draw.io or written in a text format.
+//!
+//! Every automaton type implements the [`Automaton`](shared::automaton::Automaton) trait,
+//! [`parse_automaton`](shared::parsing::parse_automaton) reads one from a file and the functions
+//! in [`evaluation`](shared::evaluation) compare automatons with each other or with test cases.
+//!
+//! ```
+//! use automatonator::shared::parsing::parse_automaton;
+//!
+//! let dfa = parse_automaton("data/dfa/ends-with-a-dfa.txt", None).expect("file missing");
+//! assert!(dfa.accepts("ba"));
+//! ```
+
+pub mod automatons;
+pub mod shared;
+pub mod tests;
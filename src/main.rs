@@ -1,126 +1,617 @@
-pub mod args;
-pub mod automatons;
-pub mod shared;
-pub mod tests;
+mod args;
 
+use automatonator::shared::analysis::analyze;
+use automatonator::shared::automaton::*;
+use automatonator::shared::batch::{grade_all, to_csv, to_moodle_csv, MoodleColumns, Status};
+use automatonator::shared::budget::Budget;
+use automatonator::shared::lint::{lint, Lint};
 use log::*;
-use shared::automaton::Automaton;
+use std::fmt;
 use std::fs;
 
-use args::Args;
-use clap::Parser;
-use shared::evaluation::*;
-use shared::parsing::*;
+use args::*;
+use automatonator::shared::evaluation::*;
+use automatonator::shared::feedback::Report;
+use automatonator::shared::junit::{to_xml, TestSuite};
+use automatonator::shared::operations::Operation;
+use automatonator::shared::parsing::*;
+use automatonator::shared::rubric::{grade, Rubric};
+use std::process::ExitCode;
 use std::time::Instant;
 
+// The outcome of a command, which becomes the exit code of the process
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Outcome {
+    Pass,
+    Mismatch,
+    ParseError,
+    WriteError,
+    Exceeded,
+}
+
+impl Outcome {
+    fn from_passed(passed: bool) -> Outcome {
+        if passed {
+            Outcome::Pass
+        } else {
+            Outcome::Mismatch
+        }
+    }
+
+    // A result can't be trusted if a limit stopped a simulation, so that takes precedence
+    fn or_exceeded(self, budget: &Budget) -> Outcome {
+        if matches!(self, Outcome::Pass | Outcome::Mismatch) && budget.was_exceeded() {
+            Outcome::Exceeded
+        } else {
+            self
+        }
+    }
+
+    fn exit_code(self) -> ExitCode {
+        ExitCode::from(match self {
+            Outcome::Pass => 0,
+            Outcome::Mismatch => 1,
+            Outcome::ParseError | Outcome::WriteError => 2,
+            Outcome::Exceeded => 3,
+        })
+    }
+}
+
+// Why a command couldn't finish
+#[derive(Debug)]
+enum RunError {
+    Parse(ParseError),
+    // an output file couldn't be written
+    Write(String),
+}
+
+impl From<ParseError> for RunError {
+    fn from(error: ParseError) -> Self {
+        RunError::Parse(error)
+    }
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::Parse(error) => write!(f, "{}", error),
+            RunError::Write(message) => write!(f, "{}", message),
+        }
+    }
+}
+
 struct One {
-    automaton: Automaton,
+    automaton: Box<dyn Automaton>,
 }
 
 struct Two {
-    a1: Automaton,
-    a2: Automaton,
+    a1: Box<dyn Automaton>,
+    a2: Box<dyn Automaton>,
 }
 
 struct State<Automatons> {
     state: Automatons,
+    // filled in along the way if a feedback report was requested
+    report: Option<Report>,
+    // the results for the JUnit report
+    junit: Vec<TestSuite>,
 }
 
 impl<A> State<A> {
-    fn read_first(path: &str, atype: Option<String>) -> State<One> {
+    fn read_first(path: &str, atype: Option<String>) -> Result<State<One>, RunError> {
         info!("Reading Automaton from {}", path);
 
-        let automaton = parse_automaton(path, atype).expect("Could not read first Automaton");
+        let automaton = parse_automaton(path, atype)?;
 
         info!("Successfully read Automaton:");
         automaton.view();
 
-        State {
+        Ok(State {
             state: One { automaton },
+            report: None,
+            junit: Vec::new(),
+        })
+    }
+
+    fn write_reports(&self, args: &FeedbackArgs) -> Result<(), RunError> {
+        if let Some(path) = &args.junit {
+            write_file(path, &to_xml(&self.junit))?;
+            info!("Wrote the JUnit report to {}", path);
+        }
+        let (Some(report), Some(path)) = (&self.report, &args.feedback) else {
+            return Ok(());
+        };
+        let text = report.render(args.feedback_format);
+        if path == "-" {
+            print!("{}", text);
+        } else {
+            write_file(path, &text)?;
+            info!("Wrote the feedback to {}", path);
         }
+        Ok(())
     }
 }
 
 impl State<One> {
+    // Starts the feedback report with what was read from the file
+    fn with_feedback(mut self, args: &FeedbackArgs, path: &str) -> Result<State<One>, RunError> {
+        if args.feedback.is_some() {
+            let data = read_automaton_data(path)?;
+            self.report = Some(Report::new(path, &data, self.state.automaton.as_ref()));
+        }
+        Ok(self)
+    }
+
     fn read_second(
         self,
-        path: Option<String>,
+        path: &str,
         atype1: Option<String>,
         atype2: Option<String>,
-    ) -> Option<State<Two>> {
-        if let Some(path) = path {
-            info!("Reading Second Automaton from {}", path);
-
-            if let Some(a2) = parse_automaton(&path, if atype2.is_some() { atype2 } else { atype1 })
-            {
-                info!("Successfully read Second Automaton:");
-                a2.view();
-
-                Some(State {
-                    state: Two {
-                        a1: self.state.automaton,
-                        a2,
-                    },
-                })
-            } else {
-                warn!("Could not read Second Automaton, Skipping.");
-                None
-            }
-        } else {
-            None
+    ) -> Result<State<Two>, RunError> {
+        info!("Reading Second Automaton from {}", path);
+
+        let a2 = parse_automaton(path, if atype2.is_some() { atype2 } else { atype1 })?;
+        info!("Successfully read Second Automaton:");
+        a2.view();
+
+        Ok(State {
+            state: Two {
+                a1: self.state.automaton,
+                a2,
+            },
+            report: self.report,
+            junit: self.junit,
+        })
+    }
+
+    fn cases(&mut self, path: &str, budget: &Budget) -> Result<Outcome, RunError> {
+        let cases = fs::read_to_string(path)
+            .map_err(|error| ParseError(format!("could not read {}: {}", path, error)))?;
+        info!("Evaluating Test Cases:");
+        let cases = run_tests(self.state.automaton.as_ref(), &cases, budget)?;
+        // words without an expected result can't fail
+        let (passed, total) = report_tests(&cases);
+        self.junit.push(TestSuite::from_tests(&cases));
+        if let Some(report) = &mut self.report {
+            report.tests = Some(cases);
         }
+        Ok(Outcome::from_passed(passed == total))
     }
 
-    fn cases(&self, cases: Option<String>) -> &State<One> {
-        // Test if Testcase File is given
-        if let Some(cases) = cases.and_then(|path| fs::read_to_string(path).ok()) {
-            info!("Evaluating Test Cases:");
-            fixed_test(&self.state.automaton, &cases);
+    fn analyze(&self, config: &GenerationConfig, max_length: usize) -> &State<One> {
+        let automaton = self.state.automaton.as_ref();
+        let alphabet = config
+            .alphabet
+            .clone()
+            .unwrap_or_else(|| automaton.alphabet().to_vec());
+        match analyze(automaton, &alphabet, max_length) {
+            Some(analysis) => println!("{}", analysis),
+            None => warn!("The language of this automaton type can't be analyzed"),
         }
         self
     }
 }
 
 impl State<Two> {
-    fn evaluate(&self, eval_file: Option<String>) -> &State<Two> {
+    fn evaluate(
+        &mut self,
+        eval_file: Option<String>,
+        config: &GenerationConfig,
+        budget: &Budget,
+    ) -> Result<Outcome, RunError> {
         info!("Comparing Automatons");
         // Evaluate if evaluation_file given
         if let Some(evaluation_file) = eval_file {
-            let cases =
-                fs::read_to_string(&evaluation_file).expect("evaluation file doesn't exist");
-
-            println!(
-                "Automaton reached {}% Points",
-                full_comparison(&self.state.a1, &self.state.a2, &cases)
-            );
-        } else if generated_comparison(&self.state.a1, &self.state.a2) == 1 {
-            info!("passed generated comparison")
+            let cases = fs::read_to_string(&evaluation_file).map_err(|error| {
+                ParseError(format!("could not read {}: {}", evaluation_file, error))
+            })?;
+            let grading = grade_evaluation(
+                self.state.a1.as_ref(),
+                self.state.a2.as_ref(),
+                &cases,
+                config,
+                budget,
+            )?;
+            self.junit.push(TestSuite::from_grading(&grading));
+            let points = grading.score;
+            println!("Automaton reached {}% Points", points);
+            if let Some(report) = &mut self.report {
+                report.score = Some((points * 100.0, 100.0));
+                report.counterexample = Some(counterexample(
+                    self.state.a1.as_ref(),
+                    self.state.a2.as_ref(),
+                    config,
+                    budget,
+                ));
+            }
+            Ok(Outcome::from_passed(points >= 1.0))
         } else {
-            warn!("did not pass generated comparison")
+            let witness = counterexample(
+                self.state.a1.as_ref(),
+                self.state.a2.as_ref(),
+                config,
+                budget,
+            );
+            let outcome = match witness {
+                None => {
+                    info!("passed generated comparison");
+                    Outcome::Pass
+                }
+                Some(Witness::Differs(_)) => {
+                    warn!("did not pass generated comparison");
+                    Outcome::Mismatch
+                }
+                Some(Witness::Exceeded(..)) => {
+                    warn!("generated comparison stopped by a limit");
+                    Outcome::Exceeded
+                }
+            };
+            self.junit
+                .push(TestSuite::from_counterexample(witness.as_ref()));
+            if let Some(report) = &mut self.report {
+                report.counterexample = Some(witness);
+            }
+            Ok(outcome)
         }
-        self
     }
 }
 
-fn main() {
-    let now = Instant::now();
-    let args = Args::parse();
-    colog::init();
+// Writes automatons in the text format, building a new one first if an operation is given
+fn convert(args: &ConvertArgs) -> Result<Outcome, RunError> {
+    let Some(operation) = args.operation else {
+        let data = read_automaton_data(&args.automaton)?;
+        write_text(&to_text(&data), args.output.as_deref())?;
+        return Ok(Outcome::Pass);
+    };
+    let a1 = parse_automaton(&args.automaton, args.automaton_type.clone())?;
+    let a2 = if operation.is_binary() {
+        let path = args
+            .automaton2
+            .as_ref()
+            .ok_or(ParseError::new("the operation needs a second automaton"))?;
+        let atype = args
+            .ref_automaton_type
+            .clone()
+            .or(args.automaton_type.clone());
+        Some(parse_automaton(path, atype)?)
+    } else {
+        None
+    };
+    let config = args.alphabet.generation_config()?;
+    let alphabet = match &a2 {
+        Some(a2) => config.shared_alphabet(a1.as_ref(), a2.as_ref()),
+        None => config
+            .alphabet
+            .clone()
+            .unwrap_or_else(|| a1.alphabet().to_vec()),
+    };
+    let Some(data) = operation.apply(a1.as_ref(), a2.as_deref(), &alphabet) else {
+        return Err(ParseError(format!("{:?} only works on dfas and nfas", operation)).into());
+    };
+    let text = to_text(&data);
+    build_automaton(operation.result_type(), data)?.view();
+    write_text(&text, args.output.as_deref())?;
+    Ok(Outcome::Pass)
+}
+
+fn write_text(text: &str, output: Option<&str>) -> Result<(), RunError> {
+    match output {
+        Some(path) => {
+            write_file(path, text)?;
+            info!("Wrote the automaton to {}", path);
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+// Write errors end the run with the same exit code as unreadable files instead of panicking
+fn write_file(path: &str, text: &str) -> Result<(), RunError> {
+    fs::write(path, text)
+        .map_err(|error| RunError::Write(format!("could not write {}: {}", path, error)))
+}
+
+// Reports mistakes in the structure, and if the automaton can't accept anything
+fn lint_automaton(args: &AutomatonArgs) -> Result<Outcome, RunError> {
+    let data = read_automaton_data(&args.automaton)?;
+    let lints = lint(&data);
+    lints.iter().for_each(|lint| warn!("{}", lint));
+    if lints.contains(&Lint::NoStartState) {
+        return Ok(Outcome::Mismatch);
+    }
+    let automaton_type = match &args.automaton_type {
+        Some(automaton_type) => automaton_type.clone(),
+        None => path_to_automaton_type(&args.automaton)?,
+    };
+    let automaton = build_automaton(determine_automaton_type(&automaton_type)?, data)?;
+    let empty = match automaton.to_regular(automaton.alphabet()) {
+        Some(dfa) => dfa.shortest_word().is_none(),
+        None => automaton.shortest_word() == Some(None),
+    };
+    if empty {
+        warn!("the automaton accepts no word");
+    } else if lints.is_empty() {
+        info!("no problems found");
+    }
+    Ok(Outcome::from_passed(!empty && lints.is_empty()))
+}
+
+fn trace(args: &TraceArgs) -> Result<Outcome, RunError> {
+    let state = State::<One>::read_first(
+        &args.automaton.automaton,
+        args.automaton.automaton_type.clone(),
+    )?;
+    let automaton = state.state.automaton.as_ref();
+    let word = if args.word == "ε" { "" } else { &args.word };
+    match automaton.trace(word) {
+        Some(steps) => steps.iter().for_each(|step| println!("{}", step)),
+        None => warn!("runs of this automaton type can't be traced"),
+    }
+    Ok(
+        match automaton.run(word, &mut args.limits.budget().limit()) {
+            Ok(Response::Accept(true)) => {
+                println!("accepted");
+                Outcome::Pass
+            }
+            Ok(Response::Accept(false)) => {
+                println!("rejected");
+                Outcome::Mismatch
+            }
+            Ok(Response::Output(Some(output))) => {
+                println!("output: {}", output);
+                Outcome::Pass
+            }
+            Ok(Response::Output(None)) => {
+                println!("the word can't be translated");
+                Outcome::Mismatch
+            }
+            Err(exceeded) => {
+                warn!("{}", exceeded);
+                Outcome::Exceeded
+            }
+        },
+    )
+}
+
+// The positional form: the flags that are present decide what is done
+fn legacy(args: LegacyArgs) -> Result<Outcome, RunError> {
+    let config = args.generation.generation_config()?;
+    let budget = args.limits.budget();
+
+    if let Some(operation) = args.operation {
+        return convert(&ConvertArgs {
+            automaton: args.automaton,
+            automaton2: args.automaton2,
+            automaton_type: args.automaton_type,
+            ref_automaton_type: args.ref_automaton_type,
+            operation: Some(operation),
+            output: args.output,
+            alphabet: args.generation.alphabet,
+        });
+    }
 
     // Read Single Automaton
-    let state = State::<One>::read_first(&args.automaton, args.automaton_type.clone());
+    let mut state = State::<One>::read_first(&args.automaton, args.automaton_type.clone())?
+        .with_feedback(&args.feedback, &args.automaton)?;
     // Test Test Cases if given
-    state.cases(args.testcase_file);
+    let outcome = match &args.testcase_file {
+        Some(path) => state.cases(path, &budget)?,
+        None => Outcome::Pass,
+    };
+    // Analyze the language if requested
+    if args.analyze {
+        state.analyze(&config, args.count_length);
+    }
 
     // Compare to Reference Automaton (if given)
-    if let Some(state) = state.read_second(
-        args.automaton2,
-        args.automaton_type,
-        args.ref_automaton_type,
-    ) {
-        state.evaluate(args.evaluation_file);
+    let outcome = if let Some(path) = args.automaton2 {
+        let mut state = state.read_second(&path, args.automaton_type, args.ref_automaton_type)?;
+        let outcome = outcome.max(state.evaluate(args.evaluation_file, &config, &budget)?);
+        state.write_reports(&args.feedback)?;
+        outcome
+    } else {
+        state.write_reports(&args.feedback)?;
+        outcome
+    };
+    Ok(outcome.or_exceeded(&budget))
+}
+
+// Reads both automatons and compares them, graded with the evaluation file if given
+fn compare(
+    automatons: PairArgs,
+    eval_file: Option<String>,
+    limits: &LimitArgs,
+    feedback: &FeedbackArgs,
+    generation: &GenerationArgs,
+) -> Result<Outcome, RunError> {
+    let budget = limits.budget();
+    let mut state =
+        State::<One>::read_first(&automatons.automaton, automatons.automaton_type.clone())?
+            .with_feedback(feedback, &automatons.automaton)?
+            .read_second(
+                &automatons.automaton2,
+                automatons.automaton_type,
+                automatons.ref_automaton_type,
+            )?;
+    let outcome = state.evaluate(eval_file, &generation.generation_config()?, &budget)?;
+    state.write_reports(feedback)?;
+    Ok(outcome.or_exceeded(&budget))
+}
+
+// Gives the points of every criterion of the rubric and explains the deductions
+fn grade_rubric(args: RubricArgs) -> Result<Outcome, RunError> {
+    let rubric_file = fs::read_to_string(&args.rubric)
+        .map_err(|error| ParseError(format!("could not read {}: {}", args.rubric, error)))?;
+    let rubric = Rubric::parse(&rubric_file)?;
+    let automatons = args.automatons;
+    let data = read_automaton_data(&automatons.automaton)?;
+    let automaton_type =
+        resolve_automaton_type(&automatons.automaton, automatons.automaton_type.clone())?;
+    let mut state =
+        State::<One>::read_first(&automatons.automaton, automatons.automaton_type.clone())?
+            .with_feedback(&args.feedback, &automatons.automaton)?
+            .read_second(
+                &automatons.automaton2,
+                automatons.automaton_type,
+                automatons.ref_automaton_type,
+            )?;
+    let budget = args.limits.budget();
+    let scores = grade(
+        &rubric,
+        &data,
+        &automaton_type,
+        state.state.a1.as_ref(),
+        state.state.a2.as_ref(),
+        &args.generation.generation_config()?,
+        &budget,
+    );
+    scores.iter().for_each(|score| println!("{}", score));
+    let points: f64 = scores.iter().map(|score| score.points).sum();
+    let max: f64 = scores.iter().map(|score| score.max).sum();
+    println!("Total: {}/{} points", points, max);
+    state.junit.push(TestSuite::from_scores(&scores));
+    if let Some(report) = &mut state.report {
+        report.score = Some((points, max));
+        report.deductions = scores;
+    }
+    state.write_reports(&args.feedback)?;
+    Ok(Outcome::from_passed(points >= max).or_exceeded(&budget))
+}
+
+// Grades every submission and writes the grades for the course platform
+fn batch(args: BatchArgs) -> Result<Outcome, RunError> {
+    let evaluation = match &args.evaluation_file {
+        Some(path) => {
+            let text = fs::read_to_string(path)
+                .map_err(|error| ParseError(format!("could not read {}: {}", path, error)))?;
+            // mistakes in the evaluation file would otherwise be reported for every submission
+            Evaluation::parse(&text)?;
+            Some(text)
+        }
+        None => None,
+    };
+    let reference_type = args
+        .ref_automaton_type
+        .clone()
+        .or(args.automaton_type.clone());
+    let reference = parse_automaton(&args.reference, reference_type)?;
+    let results = grade_all(
+        &args.submissions,
+        args.automaton_type.clone(),
+        reference.as_ref(),
+        evaluation.as_deref(),
+        &args.generation.generation_config()?,
+        || args.limits.budget(),
+        args.id_source,
+    );
+    for graded in &results {
+        match &graded.error {
+            Some(error) => warn!("{}: {}", graded.path, error),
+            None => info!("{}: {}", graded.id, graded.status),
+        }
+    }
+    let csv = to_csv(&results);
+    match &args.csv {
+        Some(path) if path == "-" => print!("{}", csv),
+        Some(path) => {
+            write_file(path, &csv)?;
+            info!("Wrote the grades to {}", path);
+        }
+        None if args.moodle.is_none() => print!("{}", csv),
+        None => {}
+    }
+    if let Some(path) = &args.moodle {
+        let columns = MoodleColumns {
+            id: args.moodle_id_column.clone(),
+            grade: args.moodle_grade_column.clone(),
+            max_grade: args.moodle_max_grade,
+        };
+        write_file(path, &to_moodle_csv(&results, &columns))?;
+        info!("Wrote the Moodle grades to {}", path);
     }
+    Ok(results
+        .iter()
+        .map(|graded| match graded.status {
+            Status::Pass => Outcome::Pass,
+            Status::Mismatch => Outcome::Mismatch,
+            Status::Error => Outcome::ParseError,
+            Status::Exceeded => Outcome::Exceeded,
+        })
+        .max()
+        .unwrap_or(Outcome::Pass))
+}
+
+fn run(cli: Result<Cli, Box<LegacyArgs>>) -> Result<Outcome, RunError> {
+    match cli {
+        Ok(Cli { command }) => match command {
+            Command::View(args) => {
+                let state = State::<One>::read_first(
+                    &args.automaton.automaton,
+                    args.automaton.automaton_type,
+                )?;
+                if args.analyze {
+                    state.analyze(&args.alphabet.generation_config()?, args.count_length);
+                }
+                Ok(Outcome::Pass)
+            }
+            Command::Check(args) => {
+                let budget = args.limits.budget();
+                let mut state = State::<One>::read_first(
+                    &args.automaton.automaton,
+                    args.automaton.automaton_type,
+                )?
+                .with_feedback(&args.feedback, &args.automaton.automaton)?;
+                let outcome = state.cases(&args.cases, &budget)?;
+                state.write_reports(&args.feedback)?;
+                Ok(outcome.or_exceeded(&budget))
+            }
+            Command::Compare(args) => compare(
+                args.automatons,
+                None,
+                &args.limits,
+                &args.feedback,
+                &args.generation,
+            ),
+            Command::Grade(args) => compare(
+                args.automatons,
+                Some(args.evaluation_file),
+                &args.limits,
+                &args.feedback,
+                &args.generation,
+            ),
+            Command::Rubric(args) => grade_rubric(args),
+            Command::Batch(args) => batch(args),
+            Command::Convert(args) => convert(&args),
+            Command::Minimize(args) => convert(&ConvertArgs {
+                automaton: args.automaton.automaton,
+                automaton2: None,
+                automaton_type: args.automaton.automaton_type,
+                ref_automaton_type: None,
+                operation: Some(Operation::Minimize),
+                output: args.output,
+                alphabet: args.alphabet,
+            }),
+            Command::Lint(args) => lint_automaton(&args),
+            Command::Trace(args) => trace(&args),
+        },
+        Err(args) => legacy(*args),
+    }
+}
+
+fn main() -> ExitCode {
+    let now = Instant::now();
+    let cli = Cli::parse_any();
+    colog::init();
+
+    let outcome = run(cli).unwrap_or_else(|error| {
+        error!("{}", error);
+        match error {
+            RunError::Parse(_) => Outcome::ParseError,
+            RunError::Write(_) => Outcome::WriteError,
+        }
+    });
 
     let elapsed = now.elapsed();
     info!("Took: {:.2?}", elapsed);
+    outcome.exit_code()
 }
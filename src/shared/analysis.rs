@@ -0,0 +1,86 @@
+use std::fmt;
+
+use super::automaton::Automaton;
+use super::utils::show_word;
+
+// How many accepted words are listed for a finite language
+const LISTED_WORDS: usize = 100;
+
+// Properties of the language of an automaton, to spot degenerate automatons quickly
+pub struct Analysis {
+    // None if the language is empty
+    pub shortest_word: Option<String>,
+    // only known for finite automatons
+    pub regular: Option<RegularAnalysis>,
+}
+
+pub struct RegularAnalysis {
+    // all accepted words if the language is finite
+    pub words: Option<Vec<String>>,
+    pub universal: bool,
+    // number of accepted words of each length
+    pub counts: Vec<u128>,
+}
+
+// None if the language of the automaton type can't be analysed
+pub fn analyze(
+    automaton: &dyn Automaton,
+    alphabet: &[char],
+    max_length: usize,
+) -> Option<Analysis> {
+    if let Some(dfa) = automaton.to_regular(alphabet) {
+        return Some(Analysis {
+            shortest_word: dfa.shortest_word(),
+            regular: Some(RegularAnalysis {
+                words: dfa.words(),
+                universal: dfa.is_universal(),
+                counts: dfa.accepted_counts(max_length),
+            }),
+        });
+    }
+    automaton.shortest_word().map(|shortest_word| Analysis {
+        shortest_word,
+        regular: None,
+    })
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Empty: {}", yes_no(self.shortest_word.is_none()))?;
+        if let Some(word) = &self.shortest_word {
+            write!(f, "\nShortest accepted word: {}", show_word(word))?;
+        }
+        if let Some(regular) = &self.regular {
+            match &regular.words {
+                Some(words) => {
+                    write!(f, "\nFinite: yes, {} words", words.len())?;
+                    if !words.is_empty() {
+                        let listed: Vec<&str> = words
+                            .iter()
+                            .take(LISTED_WORDS)
+                            .map(|w| show_word(w))
+                            .collect();
+                        write!(f, ": {}", listed.join(", "))?;
+                        if words.len() > LISTED_WORDS {
+                            write!(f, ", ...")?;
+                        }
+                    }
+                }
+                None => write!(f, "\nFinite: no")?,
+            }
+            write!(f, "\nUniversal: {}", yes_no(regular.universal))?;
+            for (length, count) in regular.counts.iter().enumerate() {
+                write!(f, "\nAccepted words of length {}: {}", length, count)?;
+            }
+        }
+        Ok(())
+    }
+}
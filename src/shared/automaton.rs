@@ -1,4 +1,7 @@
-use crate::automatons::{dfa::DFA, nfa::NFA, pda::PDA};
+use std::fmt;
+
+use super::budget::{Exceeded, Limit};
+use super::regular::RegularDfa;
 
 // The Place with all the Boilerplate
 
@@ -6,12 +9,42 @@ use crate::automatons::{dfa::DFA, nfa::NFA, pda::PDA};
 
 pub type VertexId = u32;
 
+#[derive(Clone, Debug, PartialEq)]
 pub enum AutomatonData {
     Edge(VertexId, VertexId, String),
     Final(VertexId),
     Start(VertexId),
+    // declared input alphabet
+    Alphabet(Vec<char>),
+    // declared stack alphabet
+    StackAlphabet(Vec<char>),
+    // output of a state (used by moore automatons)
+    Output(VertexId, String),
+    // weight a word needs to be accepted (used by weighted automatons)
+    Threshold(f64),
+    // name of a state as it was written in the file
+    Name(VertexId, String),
+}
+
+// Why a file couldn't be turned into an automaton
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError(pub String);
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        ParseError(message.into())
+    }
 }
 
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
 // AutomatonType
 
 #[allow(clippy::upper_case_acronyms)]
@@ -19,50 +52,143 @@ pub enum AutomatonType {
     DFA,
     NFA,
     PDA,
+    Mealy,
+    Moore,
+    Buchi,
+    TwoDFA,
+    LBA,
+    Counter,
+    WFA,
 }
 
-pub fn determine_automaton_type(typestr: &str) -> AutomatonType {
-    match typestr.to_lowercase().as_str() {
+pub fn determine_automaton_type(typestr: &str) -> Result<AutomatonType, ParseError> {
+    Ok(match typestr.to_lowercase().as_str() {
         "dfa" => AutomatonType::DFA,
         "nfa" => AutomatonType::NFA,
         "pda" => AutomatonType::PDA,
-        _ => unimplemented!("type {} is not supported", typestr),
-    }
+        "mealy" => AutomatonType::Mealy,
+        "moore" => AutomatonType::Moore,
+        "buchi" | "büchi" => AutomatonType::Buchi,
+        "2dfa" => AutomatonType::TwoDFA,
+        "lba" => AutomatonType::LBA,
+        "counter" => AutomatonType::Counter,
+        "wfa" => AutomatonType::WFA,
+        _ => return Err(ParseError(format!("type {} is not supported", typestr))),
+    })
 }
 
-pub fn path_to_automaton_type(filepath: &str) -> String {
-    vec!["dfa", "nfa", "pda"]
-        .into_iter()
-        .find(|pattern| filepath.to_lowercase().contains(pattern))
-        .expect("No Automaton Type could be determined")
-        .to_string()
+pub fn path_to_automaton_type(filepath: &str) -> Result<String, ParseError> {
+    // 2dfa has to be checked before dfa
+    vec![
+        "2dfa", "dfa", "nfa", "pda", "mealy", "moore", "buchi", "lba", "counter", "wfa",
+    ]
+    .into_iter()
+    .find(|pattern| filepath.to_lowercase().contains(pattern))
+    .map(str::to_string)
+    .ok_or(ParseError::new("No Automaton Type could be determined"))
 }
 
-// The Automaton Interface Enum
+// The Automaton Interface
 
-// Macro to generate the Implementation
+/// An automaton that can be simulated on words and compared with other automatons.
+///
+/// Every automaton type of this crate implements it, custom types can be added by implementing
+/// it as well. Only the required methods have to be written, the others have defaults for
+/// acceptors of finite words. Comparisons simulate words on several threads at once,
+/// so implementations have to be `Sync`.
+pub trait Automaton: Sync {
+    /// Decides if a word is accepted, stops with an error if the limit is exceeded.
+    fn accepts_within(&self, word: &str, limit: &mut Limit) -> Result<bool, Exceeded>;
+
+    /// Decides if a word is accepted without any limit (may not halt for looping automatons).
+    fn accepts(&self, word: &str) -> bool {
+        self.accepts_within(word, &mut Limit::unlimited())
+            .unwrap_or(false)
+    }
+
+    /// The sorted input alphabet, without epsilon.
+    fn alphabet(&self) -> &[char];
+
+    /// Logs a description of the automaton.
+    fn view(&self);
+
+    /// The ids of all states, sorted.
+    fn states(&self) -> Vec<VertexId>;
+
+    /// All transitions as source, target and a label in the text format of the automaton type.
+    fn transitions(&self) -> Vec<(VertexId, VertexId, String)>;
+
+    /// An equivalent complete dfa over the given alphabet, None if the language is not regular
+    /// (or the conversion is not implemented).
+    fn to_regular(&self, _alphabet: &[char]) -> Option<RegularDfa> {
+        None
+    }
+
+    /// The shortest accepted word if it can be computed, Some(None) if the language is empty.
+    fn shortest_word(&self) -> Option<Option<String>> {
+        None
+    }
 
-macro_rules! gen_impl {
-    ($enum:ident, $function:ident, $return:ty, $($arg:ident; $atype:ty),*) => {
-        impl $enum {
-            pub fn $function(&self, $($arg: $atype),*) -> $return {
-                match self {
-                    $enum::DFA(a) => a.$function($($arg),*),
-                    $enum::NFA(a) => a.$function($($arg),*),
-                    $enum::PDA(a) => a.$function($($arg),*),
-                }
-            }
+    /// What the automaton does with a word, transducers return their translation.
+    fn run(&self, word: &str, limit: &mut Limit) -> Result<Response, Exceeded> {
+        self.accepts_within(word, limit).map(Response::Accept)
+    }
+
+    /// The states a run visits while reading a word, one line per step,
+    /// None if runs of the automaton type can't be traced.
+    fn trace(&self, _word: &str) -> Option<Vec<String>> {
+        None
+    }
+
+    /// True for automatons that translate words instead of accepting them.
+    fn is_transducer(&self) -> bool {
+        false
+    }
+
+    /// The translation of a word, None if it can't be translated or this is no transducer.
+    fn translate(&self, word: &str) -> Option<String> {
+        match self.run(word, &mut Limit::unlimited()) {
+            Ok(Response::Output(output)) => output,
+            _ => None,
         }
-    };
+    }
+
+    /// True for automatons that read infinite words written as u(v).
+    fn is_omega(&self) -> bool {
+        false
+    }
+
+    /// True for automatons that assign a weight to every word.
+    fn is_weighted(&self) -> bool {
+        false
+    }
+
+    /// The weight of a word, None if this is no weighted automaton.
+    fn weight_within(&self, _word: &str, _limit: &mut Limit) -> Result<Option<f64>, Exceeded> {
+        Ok(None)
+    }
+
+    /// The weight of a word without any limit.
+    fn weight(&self, word: &str) -> Option<f64> {
+        self.weight_within(word, &mut Limit::unlimited())
+            .unwrap_or(None)
+    }
 }
 
-#[allow(clippy::upper_case_acronyms)]
-pub enum Automaton {
-    DFA(DFA),
-    NFA(NFA),
-    PDA(PDA),
+// What an automaton does with a word: acceptors accept or reject it, transducers translate it
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Response {
+    Accept(bool),
+    // None if the word couldn't be translated
+    Output(Option<String>),
 }
 
-gen_impl!(Automaton, accepts, bool, word; &str);
-gen_impl!(Automaton, alphabet, &Vec<char>,);
-gen_impl!(Automaton, view, (),);
+impl Response {
+    pub fn accepted(&self) -> bool {
+        match self {
+            Response::Accept(accepted) => *accepted,
+            Response::Output(output) => output.is_some(),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+use std::path::Path;
+
+use super::automaton::Automaton;
+use super::budget::Budget;
+use super::evaluation::{full_comparison, generated_comparison, GenerationConfig};
+use super::parsing::parse_automaton;
+use super::utils::parallel_map;
+
+// Where the id of a student is taken from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum IdSource {
+    /// the file name without its extensions (alice.drawio.xml -> alice)
+    File,
+    /// the directory the file is in (alice/dfa.xml -> alice)
+    Directory,
+}
+
+// Longest first, so alice.drawio.xml loses both
+const EXTENSIONS: [&str; 4] = [".drawio.xml", ".xml", ".drawio", ".txt"];
+
+pub fn student_id(path: &str, source: IdSource) -> String {
+    let path = Path::new(path);
+    let name = match source {
+        IdSource::File => path.file_name(),
+        IdSource::Directory => path.parent().and_then(Path::file_name),
+    };
+    let name = name.map(|name| name.to_string_lossy()).unwrap_or_default();
+    match source {
+        IdSource::File => EXTENSIONS
+            .iter()
+            .find_map(|extension| name.strip_suffix(extension))
+            .map(str::to_string)
+            .or_else(|| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+            })
+            .unwrap_or_default(),
+        IdSource::Directory => name.to_string(),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Mismatch,
+    // a limit was exceeded, so the score may be too low
+    Exceeded,
+    // the submission couldn't be read
+    Error,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Pass => write!(f, "pass"),
+            Status::Mismatch => write!(f, "mismatch"),
+            Status::Exceeded => write!(f, "exceeded"),
+            Status::Error => write!(f, "error"),
+        }
+    }
+}
+
+// The result of grading one submission
+#[derive(Clone, Debug, PartialEq)]
+pub struct Graded {
+    pub id: String,
+    pub path: String,
+    // between 0 and 1, None if the submission couldn't be read
+    pub score: Option<f64>,
+    pub status: Status,
+    pub error: Option<String>,
+}
+
+// Grades every submission against the reference, with the evaluation file if given
+// and the generated comparison otherwise, every submission gets a new budget
+// The submissions are spread over the threads of the config, each one is graded on a single thread
+pub fn grade_all(
+    submissions: &[String],
+    automaton_type: Option<String>,
+    reference: &dyn Automaton,
+    evaluation: Option<&str>,
+    config: &GenerationConfig,
+    budget: impl Fn() -> Budget + Sync,
+    id_source: IdSource,
+) -> Vec<Graded> {
+    let single = &GenerationConfig {
+        threads: 1,
+        ..config.clone()
+    };
+    parallel_map(submissions, config.threads, |path| {
+        let budget = budget();
+        let score = parse_automaton(path, automaton_type.clone()).and_then(|automaton| {
+            let automaton = automaton.as_ref();
+            match evaluation {
+                Some(text) => full_comparison(automaton, reference, text, single, &budget),
+                None => Ok(generated_comparison(automaton, reference, single, &budget) as f64),
+            }
+        });
+        let (score, status, error) = match score {
+            Ok(score) if budget.was_exceeded() => (Some(score), Status::Exceeded, None),
+            Ok(score) if score >= 1.0 => (Some(score), Status::Pass, None),
+            Ok(score) => (Some(score), Status::Mismatch, None),
+            Err(error) => (None, Status::Error, Some(error.to_string())),
+        };
+        Graded {
+            id: student_id(path, id_source),
+            path: path.clone(),
+            score,
+            status,
+            error,
+        }
+    })
+}
+
+// One line per submission with the score in percent
+pub fn to_csv(results: &[Graded]) -> String {
+    let mut lines = vec!["id,file,score,verdict,error".to_string()];
+    lines.extend(results.iter().map(|graded| {
+        [
+            csv_field(&graded.id),
+            csv_field(&graded.path),
+            graded
+                .score
+                .map(|score| format_grade(score * 100.0))
+                .unwrap_or_default(),
+            graded.status.to_string(),
+            csv_field(graded.error.as_deref().unwrap_or_default()),
+        ]
+        .join(",")
+    }));
+    lines.join("\n") + "\n"
+}
+
+// The columns of a grade import, the id column has to match a user field of the course
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoodleColumns {
+    pub id: String,
+    pub grade: String,
+    // the grade a score of 1 is worth
+    pub max_grade: f64,
+}
+
+// Submissions that couldn't be read get no grade, so they are left out
+pub fn to_moodle_csv(results: &[Graded], columns: &MoodleColumns) -> String {
+    let mut lines = vec![format!(
+        "{},{}",
+        csv_field(&columns.id),
+        csv_field(&columns.grade)
+    )];
+    lines.extend(results.iter().filter_map(|graded| {
+        let score = graded.score?;
+        Some(format!(
+            "{},{}",
+            csv_field(&graded.id),
+            format_grade(score * columns.max_grade)
+        ))
+    }));
+    lines.join("\n") + "\n"
+}
+
+// Quotes fields that contain separators, quotes or line breaks
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Grades are rounded to two decimals
+fn format_grade(grade: f64) -> String {
+    ((grade * 100.0).round() / 100.0).to_string()
+}
@@ -0,0 +1,150 @@
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Resource limits for simulating automata, so unbounded runs (PDA, KPDA, ...) can't hang the process
+
+#[derive(Clone, Debug)]
+pub struct Budget {
+    word_time: Option<Duration>,
+    total_time: Option<Duration>,
+    max_configurations: Option<usize>,
+    started: Instant,
+    // set as soon as any limit created from this budget is exceeded
+    exceeded: Arc<AtomicBool>,
+}
+
+impl Budget {
+    pub fn new(
+        word_time: Option<Duration>,
+        total_time: Option<Duration>,
+        max_configurations: Option<usize>,
+    ) -> Self {
+        Budget {
+            word_time,
+            total_time,
+            max_configurations,
+            started: Instant::now(),
+            exceeded: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn unlimited() -> Self {
+        Budget::new(None, None, None)
+    }
+
+    // Creates the limit for simulating a single word
+    pub fn limit(&self) -> Limit {
+        let now = Instant::now();
+        let word_deadline = self.word_time.map(|t| (now + t, Exceeded::WordTime));
+        let total_deadline = self
+            .total_time
+            .map(|t| (self.started + t, Exceeded::TotalTime));
+        let deadline = match (word_deadline, total_deadline) {
+            (Some(word), Some(total)) => Some(if word.0 < total.0 { word } else { total }),
+            (word, total) => word.or(total),
+        };
+        Limit {
+            deadline,
+            max_configurations: self.max_configurations,
+            configurations: 0,
+            exceeded: Some(self.exceeded.clone()),
+        }
+    }
+
+    // true if the time for the whole submission is used up, which counts as exceeding it
+    pub fn exhausted(&self) -> bool {
+        let exhausted = self
+            .total_time
+            .is_some_and(|total| self.started.elapsed() >= total);
+        if exhausted {
+            self.exceeded.store(true, Ordering::Relaxed);
+        }
+        exhausted
+    }
+
+    // A budget with the same limits that doesn't report exceeded limits to this one,
+    // for words that are simulated ahead of time and may turn out to be unneeded
+    pub fn detached(&self) -> Budget {
+        Budget {
+            exceeded: Arc::new(AtomicBool::new(false)),
+            ..self.clone()
+        }
+    }
+
+    pub fn mark_exceeded(&self) {
+        self.exceeded.store(true, Ordering::Relaxed);
+    }
+
+    // true if a simulation was stopped by a limit, so results may be incomplete
+    pub fn was_exceeded(&self) -> bool {
+        self.exceeded.load(Ordering::Relaxed)
+    }
+}
+
+// Tracks the resources used while simulating a single word
+
+pub struct Limit {
+    deadline: Option<(Instant, Exceeded)>,
+    max_configurations: Option<usize>,
+    configurations: usize,
+    // shared with the budget the limit was created from
+    exceeded: Option<Arc<AtomicBool>>,
+}
+
+impl Limit {
+    pub fn unlimited() -> Self {
+        Limit {
+            deadline: None,
+            max_configurations: None,
+            configurations: 0,
+            exceeded: None,
+        }
+    }
+
+    // Should be called for every configuration the simulation visits
+    pub fn step(&mut self) -> Result<(), Exceeded> {
+        self.check().inspect_err(|_| {
+            if let Some(exceeded) = &self.exceeded {
+                exceeded.store(true, Ordering::Relaxed);
+            }
+        })
+    }
+
+    fn check(&mut self) -> Result<(), Exceeded> {
+        self.configurations += 1;
+        if self
+            .max_configurations
+            .is_some_and(|max| self.configurations > max)
+        {
+            return Err(Exceeded::Configurations);
+        }
+        // checking the clock is comparatively expensive, so only do it every few steps
+        if self.configurations.is_multiple_of(256) {
+            if let Some((deadline, reason)) = self.deadline {
+                if Instant::now() >= deadline {
+                    return Err(reason);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exceeded {
+    WordTime,
+    TotalTime,
+    Configurations,
+}
+
+impl Display for Exceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Exceeded::WordTime => write!(f, "time limit per word exceeded"),
+            Exceeded::TotalTime => write!(f, "total time limit exceeded"),
+            Exceeded::Configurations => write!(f, "configuration limit exceeded"),
+        }
+    }
+}
@@ -1,113 +1,891 @@
+use std::ops::Range;
+
 use log::{info, warn};
-use rand::prelude::*;
-use rand_chacha::ChaCha8Rng;
 
-use super::automaton::Automaton;
+use super::automaton::{Automaton, ParseError, Response};
+use super::budget::{Budget, Exceeded};
+use super::regular::RegularDfa;
+use super::sampling::WordSampler;
+use super::utils::{parallel_map, parse_output, parse_weight, show_word, union_alphabet};
+
+// Result of running two automatons on the same word
+pub enum Verdict {
+    Agree,
+    Disagree,
+    Exceeded(Exceeded),
+}
+
+// A word on which two automatons couldn't be shown to agree
+#[derive(Clone, Debug, PartialEq)]
+pub enum Witness {
+    Differs(String),
+    // a limit stopped the simulation, so the automatons may still agree
+    Exceeded(String, Exceeded),
+}
+
+impl Witness {
+    pub fn word(&self) -> &str {
+        match self {
+            Witness::Differs(word) | Witness::Exceeded(word, _) => word,
+        }
+    }
+}
 
-pub fn fixed_test(automaton: &Automaton, wordlist: &str) -> u64 {
+// Runs both automatons on a word with one shared limit
+pub fn run_both(
+    automaton1: &dyn Automaton,
+    automaton2: &dyn Automaton,
+    word: &str,
+    budget: &Budget,
+) -> Result<(Response, Response), Exceeded> {
+    if budget.exhausted() {
+        return Err(Exceeded::TotalTime);
+    }
+    let mut limit = budget.limit();
+    let response1 = automaton1.run(word, &mut limit)?;
+    let response2 = automaton2.run(word, &mut limit)?;
+    Ok((response1, response2))
+}
+
+pub fn compare_word(
+    automaton1: &dyn Automaton,
+    automaton2: &dyn Automaton,
+    word: &str,
+    budget: &Budget,
+) -> Verdict {
+    to_verdict(
+        &run_both(automaton1, automaton2, word, budget),
+        Relation::Equal,
+    )
+}
+
+fn to_verdict(result: &Result<(Response, Response), Exceeded>, relation: Relation) -> Verdict {
+    match result {
+        Ok((response1, response2)) if relation.holds(response1, response2) => Verdict::Agree,
+        Ok(_) => Verdict::Disagree,
+        Err(reason) => Verdict::Exceeded(*reason),
+    }
+}
+
+// The result of one line of a test file
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestCase {
+    pub word: String,
+    // None if the line only lists the word, or the expected result is unknown
+    pub expected: Option<String>,
+    pub actual: String,
+    pub passed: bool,
+}
+
+impl TestCase {
+    fn new(word: String, expected: Option<String>, actual: String) -> TestCase {
+        let passed = expected.as_ref().is_none_or(|expected| *expected == actual);
+        TestCase {
+            word,
+            expected,
+            actual,
+            passed,
+        }
+    }
+}
+
+// Runs the automaton on every line of a test file, see fixed_test for the formats
+pub fn run_tests(
+    automaton: &dyn Automaton,
+    wordlist: &str,
+    budget: &Budget,
+) -> Result<Vec<TestCase>, ParseError> {
+    if automaton.is_transducer() {
+        return Ok(translation_tests(automaton, wordlist, budget));
+    }
+    if automaton.is_weighted() {
+        return weight_tests(automaton, wordlist, budget);
+    }
+    Ok(wordlist
+        .lines()
+        .map(|line| {
+            let (word, expected) = parse_expectation(line);
+            let actual = match automaton.accepts_within(&word, &mut budget.limit()) {
+                Ok(true) => "accept".to_string(),
+                Ok(false) => "reject".to_string(),
+                Err(reason) => format!("exceeded ({})", reason),
+            };
+            TestCase::new(word, expected.map(str::to_string), actual)
+        })
+        .collect())
+}
+
+// Splits a line like "+ aab", "- ba" or "aab accept" into the word and the expected verdict,
+// any other line is a word without an expected verdict
+fn parse_expectation(line: &str) -> (String, Option<&'static str>) {
+    let items: Vec<_> = line.split_whitespace().collect();
+    match items[..] {
+        ["+"] => (String::new(), Some("accept")),
+        ["-"] => (String::new(), Some("reject")),
+        ["+", word] | [word, "accept"] => (parse_output(word), Some("accept")),
+        ["-", word] | [word, "reject"] => (parse_output(word), Some("reject")),
+        _ => (line.to_string(), None),
+    }
+}
+
+// Tests the automaton against a file with one word per line and reports the results.
+// For acceptors a line can state the expected verdict, for transducers and weighted automatons
+// the expected output or weight is required.
+// Returns the number of passed tests and of all tests with an expected result
+pub fn fixed_test(
+    automaton: &dyn Automaton,
+    wordlist: &str,
+    budget: &Budget,
+) -> Result<(u64, u64), ParseError> {
+    Ok(report_tests(&run_tests(automaton, wordlist, budget)?))
+}
+
+// Prints every test, a summary and the failed tests as a diff of the expected and the actual results
+// Returns the number of passed tests and of all tests with an expected result
+pub fn report_tests(cases: &[TestCase]) -> (u64, u64) {
+    for case in cases {
+        let word = show_word(&case.word);
+        match &case.expected {
+            None => println!("'{}' -> {}", word, case.actual),
+            Some(_) if case.passed => println!("Passed: '{}' -> {}", word, case.actual),
+            Some(expected) => println!(
+                "Failed: '{}' -> {}, expected {}",
+                word, case.actual, expected
+            ),
+        }
+    }
+    let total = cases.iter().filter(|case| case.expected.is_some()).count() as u64;
+    let failed: Vec<_> = cases.iter().filter(|case| !case.passed).collect();
+    if total > 0 {
+        println!(
+            "{} passed, {} failed",
+            total - failed.len() as u64,
+            failed.len()
+        );
+    }
+    if !failed.is_empty() {
+        println!("--- expected\n+++ actual");
+        for case in &failed {
+            let word = show_word(&case.word);
+            println!("-{} {}", word, case.expected.as_deref().unwrap_or_default());
+            println!("+{} {}", word, case.actual);
+        }
+    }
+    (total - failed.len() as u64, total)
+}
+
+// How a response is shown in test results
+fn describe(response: &Response) -> String {
+    match response {
+        Response::Accept(true) => "accept".to_string(),
+        Response::Accept(false) => "reject".to_string(),
+        Response::Output(Some(output)) => show_word(output).to_string(),
+        Response::Output(None) => "no translation".to_string(),
+    }
+}
+
+// Tests a transducer against lines of the format "input output", where ε stands for the empty word
+fn translation_tests(automaton: &dyn Automaton, wordlist: &str, budget: &Budget) -> Vec<TestCase> {
     wordlist
         .lines()
-        .map(|word| {
-            if automaton.accepts(word) {
-                println!("Accepted: '{}'", word);
-                1
-            } else {
-                println!("Rejected: '{}'", word);
-                0
-            }
+        .filter_map(|line| {
+            let mut items = line.split_whitespace();
+            let input = parse_output(items.next()?);
+            let expected = parse_output(items.next().unwrap_or(""));
+            let actual = match automaton.run(&input, &mut budget.limit()) {
+                Ok(response) => describe(&response),
+                Err(reason) => format!("exceeded ({})", reason),
+            };
+            Some(TestCase::new(
+                input,
+                Some(show_word(&expected).to_string()),
+                actual,
+            ))
         })
-        .sum()
+        .collect()
 }
 
-pub fn fixed_comparison(
-    automaton1: &Automaton,
-    automaton2: &Automaton,
+// Tests a weighted automaton against lines of the format "word expected_weight tolerance",
+// where ε stands for the empty word and the tolerance defaults to 1e-9
+fn weight_tests(
+    automaton: &dyn Automaton,
     wordlist: &str,
-) -> (u64, u64) {
+    budget: &Budget,
+) -> Result<Vec<TestCase>, ParseError> {
     wordlist
         .lines()
-        .filter_map(|testcase| {
-            let mut items = testcase.split_whitespace();
-            if let Some(word) = items.next() {
-                let points = items
-                    .next()
-                    .expect("no point value given")
-                    .parse::<u64>()
-                    .expect("points value not a number");
-                if automaton1.accepts(word) == automaton2.accepts(word) {
-                    Some((points, points))
-                } else {
-                    Some((0, points))
+        .filter_map(|line| {
+            let mut items = line.split_whitespace();
+            let word = parse_output(items.next()?);
+            let Some(expected) = items.next().and_then(parse_weight) else {
+                return Some(Err(ParseError(format!(
+                    "no expected weight given in '{}'",
+                    line
+                ))));
+            };
+            let tolerance = items.next().and_then(parse_weight).unwrap_or(1e-9);
+            let (actual, passed) = match automaton.weight_within(&word, &mut budget.limit()) {
+                Ok(weight) => {
+                    let weight = weight.unwrap_or(0.0);
+                    (weight.to_string(), (weight - expected).abs() <= tolerance)
                 }
+                Err(reason) => (format!("exceeded ({})", reason), false),
+            };
+            Some(Ok(TestCase {
+                word,
+                expected: Some(expected.to_string()),
+                actual,
+                passed,
+            }))
+        })
+        .collect()
+}
+
+// An evaluation file: words worth some points each, optionally in groups with a cap,
+// and the weight of the generated comparison
+#[derive(Clone, Debug, PartialEq)]
+pub struct Evaluation {
+    pub groups: Vec<Group>,
+    pub generated_weight: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Group {
+    // None for the words in front of the first group
+    pub name: Option<String>,
+    // at most this many points are given for the group
+    pub cap: Option<f64>,
+    pub words: Vec<(String, f64)>,
+}
+
+impl Evaluation {
+    // Parses lines like "aab 2", "ε 0.5", "[group] 3" (a group with at most 3 points),
+    // "@generated 2" (the weight of the generated comparison) or "# comment"
+    pub fn parse(text: &str) -> Result<Evaluation, ParseError> {
+        let mut evaluation = Evaluation {
+            groups: vec![Group {
+                name: None,
+                cap: None,
+                words: Vec::new(),
+            }],
+            generated_weight: 1.0,
+        };
+        let invalid = |what: &str, line: &str| ParseError(format!("{} in '{}'", what, line));
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[') {
+                let (name, cap) = header
+                    .split_once(']')
+                    .ok_or(invalid("unclosed group name", line))?;
+                let cap = match cap.trim() {
+                    "" => None,
+                    cap => Some(parse_points(cap).ok_or(invalid("invalid group cap", line))?),
+                };
+                evaluation.groups.push(Group {
+                    name: Some(name.trim().to_string()),
+                    cap,
+                    words: Vec::new(),
+                });
+            } else if let Some(weight) = line.strip_prefix("@generated") {
+                evaluation.generated_weight =
+                    parse_points(weight).ok_or(invalid("invalid generated weight", line))?;
             } else {
-                println!("skipped an empty line");
-                None
+                let items: Vec<_> = line.split_whitespace().collect();
+                let [word, points] = items[..] else {
+                    return Err(invalid("expected a word and its points", line));
+                };
+                let points = parse_points(points).ok_or(invalid("invalid point value", line))?;
+                if let Some(group) = evaluation.groups.last_mut() {
+                    group.words.push((parse_output(word), points));
+                }
+            }
+        }
+        Ok(evaluation)
+    }
+}
+
+// Points, caps and weights, NaN, infinite or negative values would distort the score
+fn parse_points(input: &str) -> Option<f64> {
+    parse_weight(input).filter(|points| points.is_finite() && *points >= 0.0)
+}
+
+// Runs both automatons on the words of every group, the reference (automaton2) gives the expected results
+pub fn compare_evaluation(
+    automaton1: &dyn Automaton,
+    automaton2: &dyn Automaton,
+    evaluation: &Evaluation,
+    budget: &Budget,
+) -> Vec<Vec<TestCase>> {
+    evaluation
+        .groups
+        .iter()
+        .map(|group| {
+            group
+                .words
+                .iter()
+                .map(
+                    |(word, _)| match run_both(automaton1, automaton2, word, budget) {
+                        Ok((response1, response2)) => TestCase {
+                            word: word.clone(),
+                            expected: Some(describe(&response2)),
+                            actual: describe(&response1),
+                            passed: response1 == response2,
+                        },
+                        Err(reason) => {
+                            warn!("{} on word '{}'", reason, show_word(word));
+                            TestCase {
+                                word: word.clone(),
+                                expected: None,
+                                actual: format!("exceeded ({})", reason),
+                                passed: false,
+                            }
+                        }
+                    },
+                )
+                .collect()
+        })
+        .collect()
+}
+
+// Returns the reached and the maximal points of the words in the evaluation
+pub fn fixed_comparison(
+    automaton1: &dyn Automaton,
+    automaton2: &dyn Automaton,
+    evaluation: &Evaluation,
+    budget: &Budget,
+) -> (f64, f64) {
+    let results = compare_evaluation(automaton1, automaton2, evaluation, budget);
+    score_words(evaluation, &results)
+}
+
+fn score_words(evaluation: &Evaluation, results: &[Vec<TestCase>]) -> (f64, f64) {
+    evaluation
+        .groups
+        .iter()
+        .zip(results)
+        .map(|(group, cases)| {
+            let (reached, max) = group.words.iter().zip(cases).fold(
+                (0.0, 0.0),
+                |(reached, max), ((_, points), case)| {
+                    let reached_points = if case.passed { *points } else { 0.0 };
+                    (reached + reached_points, max + points)
+                },
+            );
+            let cap = group.cap.unwrap_or(f64::INFINITY);
+            let (reached, max) = (reached.min(cap), max.min(cap));
+            if let Some(name) = &group.name {
+                println!("{}: {}/{} points", name, reached, max);
             }
+            (reached, max)
         })
-        .fold((0, 0), |(reached, max), (new_reached, new_max)| {
+        .fold((0.0, 0.0), |(reached, max), (new_reached, new_max)| {
             (reached + new_reached, max + new_max)
         })
 }
 
-pub fn generated_comparison(automaton1: &Automaton, automaton2: &Automaton) -> u64 {
-    let alphabet = automaton2.alphabet();
+// The result of grading with an evaluation file
+pub struct Grading {
+    // the name of every group with the results of its words, in the order of the file
+    pub words: Vec<(Option<String>, Vec<TestCase>)>,
+    // the fraction of the generated words the automatons agree on, None if it doesn't count
+    pub generated: Option<f64>,
+    // between 0 and 1
+    pub score: f64,
+}
+
+// Parameters for the words used in generated_comparison
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum LengthDistribution {
+    /// every length is equally likely
+    Uniform,
+    /// shorter words are more likely
+    Short,
+    /// longer words are more likely
+    Long,
+}
+
+// How the language of the first automaton has to relate to the one of the second
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Relation {
+    /// both accept the same words (and transducers translate them the same way)
+    Equal,
+    /// every word the first accepts is accepted by the second
+    Subset,
+    /// every word the second accepts is accepted by the first
+    Superset,
+}
+
+impl Relation {
+    fn holds(&self, response1: &Response, response2: &Response) -> bool {
+        match self {
+            Relation::Equal => response1 == response2,
+            _ => self.holds_accepting(response1.accepted(), response2.accepted()),
+        }
+    }
+
+    fn holds_accepting(&self, accepted1: bool, accepted2: bool) -> bool {
+        match self {
+            Relation::Equal => accepted1 == accepted2,
+            Relation::Subset => !accepted1 || accepted2,
+            Relation::Superset => accepted1 || !accepted2,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GenerationConfig {
+    // all words up to this length are tested
+    pub exhaustive_max_length: usize,
+    // upper bound for the number of exhaustively tested words, lowers the max length for large alphabets
+    pub max_exhaustive_words: usize,
+    pub random_samples: usize,
+    pub random_lengths: Range<usize>,
+    pub distribution: LengthDistribution,
+    // weight of each length starting at 0, replaces random_lengths and distribution if not empty
+    pub length_weights: Vec<f64>,
+    // probability to test a word that is one edit away from an already tested word
+    pub boundary_bias: f64,
+    pub seed: u64,
+    // words up to this length are considered for the graded score
+    pub score_max_length: usize,
+    // explicitly given alphabet, replaces the union of both automatons alphabets
+    pub alphabet: Option<Vec<char>>,
+    pub relation: Relation,
+    // threads that simulate words at the same time, 0 uses all cores
+    pub threads: usize,
+}
+
+impl Default for GenerationConfig {
+    fn default() -> Self {
+        GenerationConfig {
+            exhaustive_max_length: 7,
+            max_exhaustive_words: 100_000,
+            random_samples: 100_000,
+            random_lengths: 0..25,
+            distribution: LengthDistribution::Uniform,
+            length_weights: Vec::new(),
+            boundary_bias: 0.0,
+            seed: 42,
+            score_max_length: 20,
+            alphabet: None,
+            relation: Relation::Equal,
+            threads: 0,
+        }
+    }
+}
+
+impl GenerationConfig {
+    // The sorted alphabet all words of a comparison are made of
+    pub fn shared_alphabet(
+        &self,
+        automaton1: &dyn Automaton,
+        automaton2: &dyn Automaton,
+    ) -> Vec<char> {
+        match &self.alphabet {
+            Some(alphabet) => union_alphabet(&[alphabet]),
+            None => union_alphabet(&[automaton1.alphabet(), automaton2.alphabet()]),
+        }
+    }
+
+    // Random words need at least one length they can have
+    pub fn validate(&self) -> Result<(), ParseError> {
+        if self.random_samples == 0 {
+            return Ok(());
+        }
+        if self.length_weights.is_empty() {
+            if self.random_lengths.is_empty() {
+                return Err(ParseError(format!(
+                    "the random word lengths {}..{} are empty, the maximum length is exclusive",
+                    self.random_lengths.start, self.random_lengths.end
+                )));
+            }
+        } else if self
+            .length_weights
+            .iter()
+            .any(|weight| !weight.is_finite() || *weight < 0.0)
+        {
+            return Err(ParseError::new(
+                "the length weights have to be non-negative numbers",
+            ));
+        } else if self.length_weights.iter().all(|weight| *weight == 0.0) {
+            return Err(ParseError::new(
+                "at least one length weight has to be positive",
+            ));
+        }
+        Ok(())
+    }
+
+    // The longest length for which all words can be tested without exceeding max_exhaustive_words
+    pub fn exhaustive_length(&self, alphabet_size: usize) -> usize {
+        let mut total: usize = 0;
+        for l in 0..=self.exhaustive_max_length {
+            total = alphabet_size
+                .checked_pow(l as u32)
+                .and_then(|count| total.checked_add(count))
+                .unwrap_or(usize::MAX);
+            if total > self.max_exhaustive_words {
+                // always test the empty word at least
+                return l.saturating_sub(1);
+            }
+        }
+        self.exhaustive_max_length
+    }
+}
+
+// Words are simulated in batches of this size on all threads and then visited in order,
+// so the results don't depend on the number of threads
+const BATCH_SIZE: usize = 1024;
+
+// Runs both automatons on all short words and on random longer words
+// visit gets the word, its kind (generated or random) and the verdict, returning false stops the iteration
+// Returns false if the iteration was stopped
+fn for_each_generated_word(
+    automaton1: &dyn Automaton,
+    automaton2: &dyn Automaton,
+    config: &GenerationConfig,
+    budget: &Budget,
+    mut visit: impl FnMut(&str, &str, Verdict) -> bool,
+) -> bool {
+    let alphabet = &config.shared_alphabet(automaton1, automaton2);
+    let max_length = config.exhaustive_length(alphabet.len());
+    if max_length < config.exhaustive_max_length {
+        info!(
+            "Alphabet of size {} too large, only testing all words up to length {}",
+            alphabet.len(),
+            max_length
+        );
+    }
+    // the words of a batch after the one that stops the iteration are simulated as well,
+    // their exceeded limits only count once the word is visited
+    let ahead = budget.detached();
+    let run_all = |words: &[String]| {
+        parallel_map(words, config.threads, |word| {
+            run_both(automaton1, automaton2, word, &ahead)
+        })
+    };
+    let mut check = |word: &str, kind: &str, result: &Result<(Response, Response), Exceeded>| {
+        if result.is_err() {
+            budget.mark_exceeded();
+        }
+        visit(word, kind, to_verdict(result, config.relation))
+    };
+    // infinite words are tested as u(v) for every way to split a finite word
+    let omega = automaton1.is_omega() || automaton2.is_omega();
     info!("Start comparing against all possible short words");
-    let passed_generated = (0..8).all(|l| {
-        (0..alphabet.len().pow(l)).all(|seed| {
-            let word = make_word(seed as u64, l as usize, alphabet);
-            let agree = automaton1.accepts(&word) == automaton2.accepts(&word);
-            if !agree {
-                warn!("did not agree on generated word '{}'", &word)
-            };
-            agree
+    // exhaustive_length made sure this doesn't overflow
+    let mut generated = (0..=max_length as u32)
+        .flat_map(|l| {
+            (0..alphabet.len().pow(l)).map(move |seed| make_word(seed as u64, l as usize, alphabet))
         })
-    });
+        .flat_map(|word| {
+            if omega {
+                let chars: Vec<char> = word.chars().collect();
+                (0..chars.len())
+                    .map(|split| {
+                        let u: String = chars[..split].iter().collect();
+                        let v: String = chars[split..].iter().collect();
+                        format!("{}({})", u, v)
+                    })
+                    .collect()
+            } else {
+                vec![word]
+            }
+        });
+    loop {
+        let words: Vec<String> = generated.by_ref().take(BATCH_SIZE).collect();
+        if words.is_empty() {
+            break;
+        }
+        let results = run_all(&words);
+        if !words
+            .iter()
+            .zip(&results)
+            .all(|(word, result)| check(word, "generated", result))
+        {
+            return false;
+        }
+    }
+    if omega && alphabet.is_empty() {
+        info!("There are no infinite words over an empty alphabet");
+        return true;
+    }
     info!("Start comparing against a random set of longer words");
-    let mut rng = ChaCha8Rng::seed_from_u64(42);
-    let passed_rng = (0..100000).all(|_| {
-        let len = rng.gen_range(0..25);
-        let seed = rng.gen_range(0..alphabet.len().pow(len)) as u64;
-        let word = make_word(seed, len as usize, alphabet);
-        let agree = automaton1.accepts(&word) == automaton2.accepts(&word);
-        if !agree {
-            warn!("did not agree on random word '{}'", &word)
-        };
-        agree
+    let mut sampler = WordSampler::new(alphabet, config);
+    let mut remaining = config.random_samples;
+    while remaining > 0 {
+        let count = remaining.min(BATCH_SIZE);
+        remaining -= count;
+        // the sampler only learns from earlier batches
+        let (words, tested): (Vec<String>, Vec<String>) = (0..count)
+            .map(|_| {
+                let word = sampler.sample();
+                let tested = if omega {
+                    sampler
+                        .lasso(&word)
+                        .expect("the alphabet of infinite words is not empty")
+                } else {
+                    word.clone()
+                };
+                (word, tested)
+            })
+            .unzip();
+        let results = run_all(&tested);
+        for ((word, tested), result) in words.into_iter().zip(&tested).zip(&results) {
+            if !check(tested, "random", result) {
+                return false;
+            }
+            if let Ok((_, reference)) = result {
+                sampler.feedback(word, reference.accepted());
+            }
+        }
+    }
+    true
+}
+
+// Returns 1 if the relation holds, decided exactly for finite automatons
+// and with the generated words otherwise
+pub fn generated_comparison(
+    automaton1: &dyn Automaton,
+    automaton2: &dyn Automaton,
+    config: &GenerationConfig,
+    budget: &Budget,
+) -> u64 {
+    match counterexample(automaton1, automaton2, config, budget) {
+        Some(_) => 0,
+        None => 1,
+    }
+}
+
+// The first automaton as a dfa and its product with the second one,
+// None if one of them isn't finite or the conversion has too many states
+fn regular_product(
+    automaton1: &dyn Automaton,
+    automaton2: &dyn Automaton,
+    alphabet: &[char],
+    accept: impl Fn(bool, bool) -> bool,
+) -> Option<(RegularDfa, RegularDfa)> {
+    let dfa1 = automaton1.to_regular(alphabet)?;
+    let product = dfa1.product(&automaton2.to_regular(alphabet)?, accept)?;
+    Some((dfa1, product))
+}
+
+// The shortest word on which the relation doesn't hold for finite automatons,
+// otherwise the first generated word on which it couldn't be confirmed
+pub fn counterexample(
+    automaton1: &dyn Automaton,
+    automaton2: &dyn Automaton,
+    config: &GenerationConfig,
+    budget: &Budget,
+) -> Option<Witness> {
+    let alphabet = &config.shared_alphabet(automaton1, automaton2);
+    if let Some((dfa1, product)) = regular_product(automaton1, automaton2, alphabet, |a, b| {
+        !config.relation.holds_accepting(a, b)
+    }) {
+        let witness = product.shortest_word();
+        if let Some(word) = &witness {
+            let accepted_by = if dfa1.accepts(word) {
+                "first"
+            } else {
+                "second"
+            };
+            warn!(
+                "the relation {:?} does not hold for '{}', it is only accepted by the {} automaton",
+                config.relation, word, accepted_by
+            );
+        }
+        return witness.map(Witness::Differs);
+    }
+    let mut witness = None;
+    for_each_generated_word(
+        automaton1,
+        automaton2,
+        config,
+        budget,
+        |word, kind, verdict| match verdict {
+            Verdict::Agree => true,
+            Verdict::Disagree => {
+                warn!("did not agree on {} word '{}'", kind, word);
+                witness = Some(Witness::Differs(word.to_string()));
+                false
+            }
+            Verdict::Exceeded(reason) => {
+                warn!("{} on {} word '{}'", reason, kind, word);
+                witness = Some(Witness::Exceeded(word.to_string(), reason));
+                false
+            }
+        },
+    );
+    witness
+}
+
+// Score between 0 and 1: the average over all lengths up to score_max_length
+// of the fraction of words of that length on which the automatons agree
+// Exact for finite automatons, estimated with the generated words otherwise
+pub fn graded_comparison(
+    automaton1: &dyn Automaton,
+    automaton2: &dyn Automaton,
+    config: &GenerationConfig,
+    budget: &Budget,
+) -> f64 {
+    let alphabet = &config.shared_alphabet(automaton1, automaton2);
+    let product = regular_product(automaton1, automaton2, alphabet, |a, b| {
+        config.relation.holds_accepting(a, b)
     });
-    if passed_generated && passed_rng {
-        1
+    // (length, fraction), lengths without generated words are left out
+    let fractions: Vec<(usize, f64)> = if let Some((_, product)) = product {
+        product
+            .accepted_fractions(config.score_max_length)
+            .into_iter()
+            .enumerate()
+            .collect()
     } else {
-        0
-    }
+        info!(
+            "Automatons can't both be converted to DFAs, estimating the score with generated words"
+        );
+        // infinite words u(v) count with the length of uv, like the words they were made of
+        let brackets = if automaton1.is_omega() || automaton2.is_omega() {
+            2
+        } else {
+            0
+        };
+        let mut per_length: Vec<(u64, u64)> = vec![(0, 0); config.score_max_length + 1];
+        for_each_generated_word(
+            automaton1,
+            automaton2,
+            config,
+            budget,
+            |word, _, verdict| {
+                let length = word.chars().count() - brackets;
+                if length > config.score_max_length {
+                    return true;
+                }
+                if let Verdict::Agree = verdict {
+                    per_length[length].0 += 1;
+                }
+                per_length[length].1 += 1;
+                true
+            },
+        );
+        per_length
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, (_, total))| total > 0)
+            .map(|(length, (agreed, total))| (length, agreed as f64 / total as f64))
+            .collect()
+    };
+    fractions.iter().for_each(|&(length, fraction)| {
+        if fraction < 1.0 {
+            warn!(
+                "agreed on {:.2}% of the words of length {}",
+                fraction * 100.0,
+                length
+            )
+        }
+    });
+    fractions.iter().map(|(_, fraction)| fraction).sum::<f64>() / fractions.len().max(1) as f64
 }
 
-pub fn full_comparison(automaton1: &Automaton, automaton2: &Automaton, wordlist: &str) -> f64 {
-    let (fixed_reached, fixed_max) = fixed_comparison(automaton1, automaton2, wordlist);
-    let generated_reached = generated_comparison(automaton1, automaton2);
-    (fixed_reached + generated_reached) as f64 / (fixed_max + 1) as f64
+pub fn full_comparison(
+    automaton1: &dyn Automaton,
+    automaton2: &dyn Automaton,
+    wordlist: &str,
+    config: &GenerationConfig,
+    budget: &Budget,
+) -> Result<f64, ParseError> {
+    grade_evaluation(automaton1, automaton2, wordlist, config, budget).map(|grading| grading.score)
+}
+
+// Like full_comparison, but with the results of every word
+pub fn grade_evaluation(
+    automaton1: &dyn Automaton,
+    automaton2: &dyn Automaton,
+    wordlist: &str,
+    config: &GenerationConfig,
+    budget: &Budget,
+) -> Result<Grading, ParseError> {
+    let evaluation = Evaluation::parse(wordlist)?;
+    let results = compare_evaluation(automaton1, automaton2, &evaluation, budget);
+    let (fixed_reached, fixed_max) = score_words(&evaluation, &results);
+    let weight = evaluation.generated_weight;
+    if fixed_max + weight <= 0.0 {
+        return Err(ParseError::new("the evaluation file gives no points"));
+    }
+    // the generated comparison is only run if it counts
+    let generated =
+        (weight > 0.0).then(|| graded_comparison(automaton1, automaton2, config, budget));
+    Ok(Grading {
+        words: evaluation
+            .groups
+            .into_iter()
+            .map(|group| group.name)
+            .zip(results)
+            .collect(),
+        generated,
+        score: (fixed_reached + weight * generated.unwrap_or(0.0)) / (fixed_max + weight),
+    })
 }
 
-pub fn make_word(seed: u64, min_length: usize, alphabet: &[char]) -> String {
+// Interprets the seed as a number in base alphabet.len() with length digits (least significant first)
+pub fn make_word(seed: u64, length: usize, alphabet: &[char]) -> String {
     let mut seed = seed;
     let s = alphabet.len() as u64;
-    let mut out = String::with_capacity(min_length);
+    let mut out = String::with_capacity(length);
 
-    while seed >= s {
-        let digit = seed % s;
-        out.push(alphabet[digit as usize]);
+    for _ in 0..length {
+        out.push(alphabet[(seed % s) as usize]);
         seed /= s;
     }
-    out.push(alphabet[seed as usize]);
-
-    while out.len() < min_length {
-        out.push(alphabet[0]);
-    }
 
     out
 }
 
+#[test]
+fn test_exhaustive_scaling() {
+    let config = GenerationConfig::default();
+    assert_eq!(config.exhaustive_length(2), 7);
+    assert_eq!(config.exhaustive_length(10), 4);
+    assert_eq!(config.exhaustive_length(usize::MAX), 0);
+}
+
+#[test]
+fn test_validate() {
+    assert!(GenerationConfig::default().validate().is_ok());
+    let invalid = [
+        // --random-min-length 30 with the default maximum
+        GenerationConfig {
+            random_lengths: Range { start: 30, end: 25 },
+            ..GenerationConfig::default()
+        },
+        GenerationConfig {
+            random_lengths: 5..5,
+            ..GenerationConfig::default()
+        },
+        GenerationConfig {
+            length_weights: vec![0.0, 0.0],
+            ..GenerationConfig::default()
+        },
+        GenerationConfig {
+            length_weights: vec![1.0, -1.0],
+            ..GenerationConfig::default()
+        },
+    ];
+    assert!(invalid.iter().all(|config| config.validate().is_err()));
+    // without random words the lengths don't matter
+    let config = GenerationConfig {
+        random_samples: 0,
+        ..invalid[0].clone()
+    };
+    assert!(config.validate().is_ok());
+}
+
 #[test]
 fn test_wordgen() {
     let a = ['a', 'b', 'c'];
     assert_eq!(make_word(25, 3, &a), "bcc");
+    assert_eq!(make_word(0, 0, &a), "");
+    assert_eq!(make_word(1, 4, &a), "baaa");
 }
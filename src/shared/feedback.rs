@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use super::automaton::*;
+use super::evaluation::{TestCase, Witness};
+use super::lint::{lint, Lint};
+use super::rubric::Score;
+use super::utils::show_word;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    Markdown,
+    Text,
+}
+
+// A report for the student who submitted an automaton,
+// the parts are filled in by the checks that were run
+pub struct Report {
+    file: String,
+    names: HashMap<VertexId, String>,
+    states: Vec<VertexId>,
+    start_states: Vec<VertexId>,
+    final_states: Vec<VertexId>,
+    transitions: Vec<(VertexId, VertexId, String)>,
+    lints: Vec<Lint>,
+    pub tests: Option<Vec<TestCase>>,
+    // Some(None) if the automaton agrees with the reference
+    pub counterexample: Option<Option<Witness>>,
+    // reached and maximal points
+    pub score: Option<(f64, f64)>,
+    pub deductions: Vec<Score>,
+}
+
+impl Report {
+    // Describes what was parsed from the file, data is what the automaton was built from
+    pub fn new(file: &str, data: &[AutomatonData], automaton: &dyn Automaton) -> Report {
+        let mut names = HashMap::new();
+        let mut start_states = Vec::new();
+        let mut final_states = Vec::new();
+        for d in data {
+            match d {
+                AutomatonData::Name(id, name) => {
+                    names.insert(*id, name.clone());
+                }
+                AutomatonData::Start(id) => start_states.push(*id),
+                AutomatonData::Final(id) => final_states.push(*id),
+                _ => {}
+            }
+        }
+        start_states.sort();
+        final_states.sort();
+        let mut transitions = automaton.transitions();
+        transitions.sort();
+        Report {
+            file: file.to_string(),
+            names,
+            states: automaton.states(),
+            start_states,
+            final_states,
+            transitions,
+            lints: lint(data),
+            tests: None,
+            counterexample: None,
+            score: None,
+            deductions: Vec::new(),
+        }
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        let markdown = format == ReportFormat::Markdown;
+        let mut out = Vec::new();
+        let heading = |out: &mut Vec<String>, title: &str| {
+            if markdown {
+                out.push(format!("\n## {}\n", title));
+            } else {
+                out.push(format!(
+                    "\n{}\n{}",
+                    title,
+                    "-".repeat(title.chars().count())
+                ));
+            }
+        };
+        let code = |text: &str| {
+            if markdown {
+                format!("`{}`", text)
+            } else {
+                format!("'{}'", text)
+            }
+        };
+        if markdown {
+            out.push(format!("# Feedback for {}", code(&self.file)));
+        } else {
+            let title = format!("Feedback for {}", self.file);
+            out.push(format!("{}\n{}", title, "=".repeat(title.chars().count())));
+        }
+
+        heading(&mut out, "Your automaton");
+        out.push(format!("- States: {}", self.state_list(&self.states)));
+        out.push(format!(
+            "- Start state: {}",
+            self.state_list(&self.start_states)
+        ));
+        out.push(format!(
+            "- Final states: {}",
+            self.state_list(&self.final_states)
+        ));
+        out.push("- Transitions:".to_string());
+        for (source, target, label) in &self.transitions {
+            out.push(format!(
+                "  - {} --{}--> {}",
+                self.name(*source),
+                label,
+                self.name(*target)
+            ));
+        }
+
+        heading(&mut out, "Warnings");
+        if self.lints.is_empty() {
+            out.push("No problems found in the structure.".to_string());
+        }
+        for lint in &self.lints {
+            out.push(format!("- {}", self.describe(lint)));
+        }
+
+        if let Some(tests) = &self.tests {
+            heading(&mut out, "Test words");
+            let checked: Vec<_> = tests
+                .iter()
+                .filter(|case| case.expected.is_some())
+                .collect();
+            let failed: Vec<_> = checked.iter().filter(|case| !case.passed).collect();
+            out.push(format!(
+                "{} of {} tests passed.",
+                checked.len() - failed.len(),
+                checked.len()
+            ));
+            if !failed.is_empty() {
+                if markdown {
+                    out.push(
+                        "\n| Word | Expected | Actual |\n| ---- | -------- | ------ |".to_string(),
+                    );
+                } else {
+                    out.push(String::new());
+                }
+                for case in failed {
+                    let expected = case.expected.as_deref().unwrap_or_default();
+                    out.push(if markdown {
+                        format!(
+                            "| {} | {} | {} |",
+                            code(show_word(&case.word)),
+                            expected,
+                            case.actual
+                        )
+                    } else {
+                        format!(
+                            "- {}: expected {}, got {}",
+                            code(show_word(&case.word)),
+                            expected,
+                            case.actual
+                        )
+                    });
+                }
+            }
+        }
+
+        if let Some(counterexample) = &self.counterexample {
+            heading(&mut out, "Comparison with the reference");
+            out.push(match counterexample {
+                Some(Witness::Differs(word)) => format!(
+                    "Your automaton and the reference differ on the word {}.",
+                    code(show_word(word))
+                ),
+                Some(Witness::Exceeded(word, reason)) => format!(
+                    "The comparison with the reference stopped on the word {}: {}.",
+                    code(show_word(word)),
+                    reason
+                ),
+                None => "Your automaton accepts the same words as the reference.".to_string(),
+            });
+        }
+
+        if let Some((reached, max)) = self.score {
+            heading(&mut out, "Score");
+            out.push(format!("{}/{} points", round(reached), round(max)));
+            if !self.deductions.is_empty() {
+                out.push(String::new());
+            }
+            for score in &self.deductions {
+                out.push(format!("- {}", score));
+            }
+        }
+        out.join("\n") + "\n"
+    }
+
+    fn name(&self, id: VertexId) -> String {
+        self.names
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    fn state_list(&self, states: &[VertexId]) -> String {
+        if states.is_empty() {
+            return "none".to_string();
+        }
+        states
+            .iter()
+            .map(|state| self.name(*state))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    // Like the lint message, but with the names of the states
+    fn describe(&self, lint: &Lint) -> String {
+        match lint {
+            Lint::NoStartState => lint.to_string(),
+            Lint::Unreachable(state) => format!("state {} can't be reached", self.name(*state)),
+            Lint::Dead(state) => format!(
+                "no final state can be reached from state {}",
+                self.name(*state)
+            ),
+            Lint::Duplicate(source, target, label) => format!(
+                "the transition {} -> {} ({}) exists more than once",
+                self.name(*source),
+                self.name(*target),
+                label
+            ),
+        }
+    }
+}
+
+// Points like 2/3 are shown with two decimals
+fn round(points: f64) -> f64 {
+    (points * 100.0).round() / 100.0
+}
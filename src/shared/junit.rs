@@ -0,0 +1,161 @@
+use super::evaluation::{Grading, TestCase, Witness};
+use super::rubric::Score;
+use super::utils::show_word;
+
+// A test case as CI servers show it, failed if there is a failure message
+#[derive(Clone, Debug, PartialEq)]
+pub struct JUnitCase {
+    pub class: String,
+    pub name: String,
+    pub failure: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestSuite {
+    pub name: String,
+    pub cases: Vec<JUnitCase>,
+}
+
+impl TestSuite {
+    // One case per word of a test file, words without an expected result pass
+    pub fn from_tests(tests: &[TestCase]) -> TestSuite {
+        let name = "test words";
+        TestSuite {
+            name: name.to_string(),
+            cases: tests.iter().map(|test| word_case(name, test)).collect(),
+        }
+    }
+
+    // One case per line of the evaluation file and one for the generated words if they count
+    pub fn from_grading(grading: &Grading) -> TestSuite {
+        let name = "evaluation";
+        let mut cases: Vec<JUnitCase> = grading
+            .words
+            .iter()
+            .flat_map(|(group, tests)| {
+                let class = group.as_deref().unwrap_or(name);
+                tests.iter().map(move |test| word_case(class, test))
+            })
+            .collect();
+        if let Some(fraction) = grading.generated {
+            cases.push(JUnitCase {
+                class: name.to_string(),
+                name: "generated words".to_string(),
+                failure: (fraction < 1.0).then(|| {
+                    format!(
+                        "agreed with the reference on {:.2}% of the generated words",
+                        fraction * 100.0
+                    )
+                }),
+            });
+        }
+        TestSuite {
+            name: name.to_string(),
+            cases,
+        }
+    }
+
+    // The generated comparison, failed if there is a witness
+    pub fn from_counterexample(witness: Option<&Witness>) -> TestSuite {
+        let name = "comparison";
+        TestSuite {
+            name: name.to_string(),
+            cases: vec![JUnitCase {
+                class: name.to_string(),
+                name: "generated words".to_string(),
+                failure: witness.map(|witness| match witness {
+                    Witness::Differs(word) => {
+                        format!("differs from the reference on '{}'", show_word(word))
+                    }
+                    Witness::Exceeded(word, reason) => {
+                        format!("{} on '{}'", reason, show_word(word))
+                    }
+                }),
+            }],
+        }
+    }
+
+    // One case per criterion, failed if points were deducted
+    pub fn from_scores(scores: &[Score]) -> TestSuite {
+        let name = "rubric";
+        TestSuite {
+            name: name.to_string(),
+            cases: scores
+                .iter()
+                .map(|score| JUnitCase {
+                    class: name.to_string(),
+                    name: score.criterion.to_string(),
+                    failure: (score.points < score.max).then(|| score.to_string()),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn failures(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| case.failure.is_some())
+            .count()
+    }
+}
+
+fn word_case(class: &str, test: &TestCase) -> JUnitCase {
+    JUnitCase {
+        class: class.to_string(),
+        name: show_word(&test.word).to_string(),
+        failure: (!test.passed).then(|| match &test.expected {
+            Some(expected) => format!("expected {}, got {}", expected, test.actual),
+            None => test.actual.clone(),
+        }),
+    }
+}
+
+pub fn to_xml(suites: &[TestSuite]) -> String {
+    let tests: usize = suites.iter().map(|suite| suite.cases.len()).sum();
+    let failures: usize = suites.iter().map(TestSuite::failures).sum();
+    let mut lines = vec![
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>".to_string(),
+        format!("<testsuites tests=\"{}\" failures=\"{}\">", tests, failures),
+    ];
+    for suite in suites {
+        lines.push(format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">",
+            escape(&suite.name),
+            suite.cases.len(),
+            suite.failures()
+        ));
+        for case in &suite.cases {
+            let attributes = format!(
+                "classname=\"{}\" name=\"{}\"",
+                escape(&case.class),
+                escape(&case.name)
+            );
+            match &case.failure {
+                Some(message) => {
+                    lines.push(format!("    <testcase {}>", attributes));
+                    lines.push(format!("      <failure message=\"{}\"/>", escape(message)));
+                    lines.push("    </testcase>".to_string());
+                }
+                None => lines.push(format!("    <testcase {}/>", attributes)),
+            }
+        }
+        lines.push("  </testsuite>".to_string());
+    }
+    lines.push("</testsuites>".to_string());
+    lines.join("\n") + "\n"
+}
+
+// Escapes text for attribute values
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            '\'' => "&apos;".to_string(),
+            '\n' => "&#10;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
@@ -0,0 +1,130 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use super::automaton::*;
+
+// Mistakes in the structure of an automaton, found without simulating it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Lint {
+    NoStartState,
+    // no path from a start state leads to the state
+    Unreachable(VertexId),
+    // no path from the state leads to a final state
+    Dead(VertexId),
+    // the same transition is drawn twice
+    Duplicate(VertexId, VertexId, String),
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lint::NoStartState => write!(f, "there is no start state"),
+            Lint::Unreachable(state) => write!(f, "state {} can't be reached", state),
+            Lint::Dead(state) => write!(f, "no final state can be reached from state {}", state),
+            Lint::Duplicate(source, target, label) => write!(
+                f,
+                "the transition {} -> {} ({}) exists more than once",
+                source, target, label
+            ),
+        }
+    }
+}
+
+// Non-final states with transitions that all loop back, like the trap state of a complete dfa
+pub fn trap_states(data: &[AutomatonData]) -> Vec<VertexId> {
+    let mut targets: HashMap<VertexId, Vec<VertexId>> = HashMap::new();
+    let mut finals = HashSet::new();
+    for d in data {
+        match d {
+            AutomatonData::Edge(source, target, _) => {
+                targets.entry(*source).or_default().push(*target)
+            }
+            AutomatonData::Final(id) => {
+                finals.insert(*id);
+            }
+            _ => {}
+        }
+    }
+    let mut traps: Vec<VertexId> = targets
+        .into_iter()
+        .filter(|(state, targets)| !finals.contains(state) && targets.iter().all(|t| t == state))
+        .map(|(state, _)| state)
+        .collect();
+    traps.sort();
+    traps
+}
+
+// Dead states are only reported if the automaton has final states,
+// automatons without them accept in other ways (empty stack, transducers)
+// Trap states (all edges are loops) are dead on purpose, complete dfas need them
+pub fn lint(data: &[AutomatonData]) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    let mut states = Vec::new();
+    let mut starts = Vec::new();
+    let mut finals = Vec::new();
+    let mut forward: HashMap<VertexId, Vec<VertexId>> = HashMap::new();
+    let mut backward: HashMap<VertexId, Vec<VertexId>> = HashMap::new();
+    let mut seen = HashSet::new();
+    for d in data {
+        match d {
+            AutomatonData::Edge(source, target, label) => {
+                states.extend([*source, *target]);
+                forward.entry(*source).or_default().push(*target);
+                backward.entry(*target).or_default().push(*source);
+                if !seen.insert((source, target, label)) {
+                    lints.push(Lint::Duplicate(*source, *target, label.clone()));
+                }
+            }
+            AutomatonData::Start(id) => {
+                states.push(*id);
+                starts.push(*id);
+            }
+            AutomatonData::Final(id) => {
+                states.push(*id);
+                finals.push(*id);
+            }
+            AutomatonData::Output(id, _) | AutomatonData::Name(id, _) => states.push(*id),
+            AutomatonData::Alphabet(_)
+            | AutomatonData::StackAlphabet(_)
+            | AutomatonData::Threshold(_) => {}
+        }
+    }
+    states.sort();
+    states.dedup();
+    if starts.is_empty() {
+        lints.insert(0, Lint::NoStartState);
+    } else {
+        let reachable = reach(&starts, &forward);
+        lints.extend(
+            states
+                .iter()
+                .filter(|state| !reachable.contains(state))
+                .map(|state| Lint::Unreachable(*state)),
+        );
+    }
+    if !finals.is_empty() {
+        let productive = reach(&finals, &backward);
+        let traps = trap_states(data);
+        lints.extend(
+            states
+                .iter()
+                .filter(|state| !productive.contains(state) && !traps.contains(state))
+                .map(|state| Lint::Dead(*state)),
+        );
+    }
+    lints
+}
+
+// All states reachable from the given ones (including them)
+fn reach(from: &[VertexId], edges: &HashMap<VertexId, Vec<VertexId>>) -> HashSet<VertexId> {
+    let mut seen: HashSet<VertexId> = from.iter().copied().collect();
+    let mut queue: VecDeque<VertexId> = from.iter().copied().collect();
+    while let Some(state) = queue.pop_front() {
+        for next in edges.get(&state).into_iter().flatten() {
+            if seen.insert(*next) {
+                queue.push_back(*next);
+            }
+        }
+    }
+    seen
+}
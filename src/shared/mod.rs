@@ -1,4 +1,14 @@
+pub mod analysis;
 pub mod automaton;
+pub mod batch;
+pub mod budget;
 pub mod evaluation;
+pub mod feedback;
+pub mod junit;
+pub mod lint;
+pub mod operations;
 pub mod parsing;
+pub mod regular;
+pub mod rubric;
+pub mod sampling;
 pub mod utils;
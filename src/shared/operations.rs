@@ -0,0 +1,167 @@
+use clap::ValueEnum;
+
+use super::automaton::*;
+use super::regular::RegularDfa;
+use super::utils::symbol_label;
+
+// Operations on regular languages, used to build reference automatons like "L1 ∩ L2"
+// The results are returned as automaton data, so they can be written to a file or turned into an automaton
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Operation {
+    Union,
+    #[value(alias = "intersect")]
+    Intersection,
+    Complement,
+    #[value(alias = "concat")]
+    Concatenation,
+    Star,
+    Minimize,
+}
+
+impl Operation {
+    // true if the operation needs two automatons
+    pub fn is_binary(&self) -> bool {
+        matches!(
+            self,
+            Operation::Union | Operation::Intersection | Operation::Concatenation
+        )
+    }
+
+    // products and complements are dfas, concatenation and star add epsilon transitions
+    pub fn result_type(&self) -> AutomatonType {
+        match self {
+            Operation::Union
+            | Operation::Intersection
+            | Operation::Complement
+            | Operation::Minimize => AutomatonType::DFA,
+            Operation::Concatenation | Operation::Star => AutomatonType::NFA,
+        }
+    }
+
+    // None if an automaton is not a finite automaton (or the second one is missing)
+    pub fn apply(
+        &self,
+        automaton1: &dyn Automaton,
+        automaton2: Option<&dyn Automaton>,
+        alphabet: &[char],
+    ) -> Option<Vec<AutomatonData>> {
+        match self {
+            Operation::Union => union(automaton1, automaton2?, alphabet),
+            Operation::Intersection => intersection(automaton1, automaton2?, alphabet),
+            Operation::Complement => complement(automaton1, alphabet),
+            Operation::Concatenation => concatenation(automaton1, automaton2?, alphabet),
+            Operation::Star => star(automaton1, alphabet),
+            Operation::Minimize => minimize(automaton1, alphabet),
+        }
+    }
+}
+
+pub fn union(
+    automaton1: &dyn Automaton,
+    automaton2: &dyn Automaton,
+    alphabet: &[char],
+) -> Option<Vec<AutomatonData>> {
+    let product = automaton1
+        .to_regular(alphabet)?
+        .product(&automaton2.to_regular(alphabet)?, |a, b| a || b)?;
+    Some(dfa_data(&product))
+}
+
+pub fn intersection(
+    automaton1: &dyn Automaton,
+    automaton2: &dyn Automaton,
+    alphabet: &[char],
+) -> Option<Vec<AutomatonData>> {
+    let product = automaton1
+        .to_regular(alphabet)?
+        .product(&automaton2.to_regular(alphabet)?, |a, b| a && b)?;
+    Some(dfa_data(&product))
+}
+
+// The complement with respect to all words over the alphabet
+pub fn complement(automaton: &dyn Automaton, alphabet: &[char]) -> Option<Vec<AutomatonData>> {
+    Some(dfa_data(&automaton.to_regular(alphabet)?.complement()))
+}
+
+// Epsilon transitions from the final states of the first automaton to the start of the second
+pub fn concatenation(
+    automaton1: &dyn Automaton,
+    automaton2: &dyn Automaton,
+    alphabet: &[char],
+) -> Option<Vec<AutomatonData>> {
+    let first = automaton1.to_regular(alphabet)?;
+    let second = automaton2.to_regular(alphabet)?;
+    let offset = first.len() as VertexId;
+    let second_start = state_id(second.start, offset);
+    let mut data = vec![
+        AutomatonData::Alphabet(alphabet.to_vec()),
+        AutomatonData::Start(state_id(first.start, 0)),
+    ];
+    data.extend(edges(&first, 0));
+    data.extend(edges(&second, offset));
+    data.extend(
+        final_states(&first, 0).map(|id| AutomatonData::Edge(id, second_start, "ε".to_string())),
+    );
+    data.extend(final_states(&second, offset).map(AutomatonData::Final));
+    Some(data)
+}
+
+// A new final start state, with epsilon transitions to the old start and back from the final states
+pub fn star(automaton: &dyn Automaton, alphabet: &[char]) -> Option<Vec<AutomatonData>> {
+    let dfa = automaton.to_regular(alphabet)?;
+    let start = dfa.len() as VertexId + 1;
+    let mut data = vec![
+        AutomatonData::Alphabet(alphabet.to_vec()),
+        AutomatonData::Start(start),
+        AutomatonData::Final(start),
+        AutomatonData::Edge(start, state_id(dfa.start, 0), "ε".to_string()),
+    ];
+    data.extend(edges(&dfa, 0));
+    data.extend(final_states(&dfa, 0).map(|id| AutomatonData::Edge(id, start, "ε".to_string())));
+    Some(data)
+}
+
+pub fn minimize(automaton: &dyn Automaton, alphabet: &[char]) -> Option<Vec<AutomatonData>> {
+    Some(dfa_data(&automaton.to_regular(alphabet)?.minimize()))
+}
+
+fn dfa_data(dfa: &RegularDfa) -> Vec<AutomatonData> {
+    let mut data = vec![
+        AutomatonData::Alphabet(dfa.alphabet.clone()),
+        AutomatonData::Start(state_id(dfa.start, 0)),
+    ];
+    data.extend(edges(dfa, 0));
+    data.extend(final_states(dfa, 0).map(AutomatonData::Final));
+    data
+}
+
+// ids start at 1, like the ones generated by the parser
+fn state_id(state: usize, offset: VertexId) -> VertexId {
+    state as VertexId + offset + 1
+}
+
+fn edges(dfa: &RegularDfa, offset: VertexId) -> impl Iterator<Item = AutomatonData> + '_ {
+    dfa.transitions
+        .iter()
+        .enumerate()
+        .flat_map(move |(state, row)| {
+            row.iter()
+                .zip(dfa.alphabet.iter())
+                .map(move |(target, symbol)| {
+                    AutomatonData::Edge(
+                        state_id(state, offset),
+                        state_id(*target, offset),
+                        symbol_label(*symbol),
+                    )
+                })
+        })
+}
+
+fn final_states(dfa: &RegularDfa, offset: VertexId) -> impl Iterator<Item = VertexId> + '_ {
+    dfa.accepting
+        .iter()
+        .enumerate()
+        .filter(|(_, accepting)| **accepting)
+        .map(move |(state, _)| state_id(state, offset))
+}
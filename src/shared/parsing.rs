@@ -1,57 +1,158 @@
-use std::{collections::HashMap, fs, vec};
+use std::{
+    collections::{HashMap, HashSet},
+    fs, vec,
+};
 
 use log::{info, warn};
 use roxmltree::Node;
 
-use crate::automatons::{dfa::DFA, nfa::NFA, pda::PDA};
+use crate::automatons::{
+    buchi::Buchi, counter::Counter, dfa::DFA, lba::LBA, mealy::Mealy, moore::Moore, nfa::NFA,
+    pda::PDA, twodfa::TwoDFA, wfa::WFA,
+};
 
 use super::automaton::*;
+use super::utils::{parse_alphabet, parse_weight};
 
-pub fn parse_automaton(filepath: &str, automaton_type: Option<String>) -> Option<Automaton> {
-    if let Some(file) = fs::read_to_string(filepath).ok() {
-        let automaton_type = determine_automaton_type(
-            &automaton_type.unwrap_or_else(|| path_to_automaton_type(filepath)),
-        );
+pub fn parse_automaton(
+    filepath: &str,
+    automaton_type: Option<String>,
+) -> Result<Box<dyn Automaton>, ParseError> {
+    let automaton_data = read_automaton_data(filepath)?;
+    build_automaton(
+        resolve_automaton_type(filepath, automaton_type)?,
+        automaton_data,
+    )
+}
 
-        let automaton_data = if filepath.ends_with(".xml") || filepath.ends_with(".drawio") {
-            parse_xml(file)
-        } else {
-            parse_text(file)
-        };
+// The given type, or the one in the file name if there is none
+pub fn resolve_automaton_type(
+    filepath: &str,
+    automaton_type: Option<String>,
+) -> Result<AutomatonType, ParseError> {
+    let automaton_type = match automaton_type {
+        Some(automaton_type) => automaton_type,
+        None => path_to_automaton_type(filepath)?,
+    };
+    determine_automaton_type(&automaton_type)
+}
 
-        Some(match automaton_type {
-            AutomatonType::DFA => Automaton::DFA(DFA::new(automaton_data)),
-            AutomatonType::NFA => Automaton::NFA(NFA::new(automaton_data)),
-            AutomatonType::PDA => Automaton::PDA(PDA::new(automaton_data)),
-        })
+// The states and transitions of a file without building an automaton
+pub fn read_automaton_data(filepath: &str) -> Result<Vec<AutomatonData>, ParseError> {
+    let file = fs::read_to_string(filepath)
+        .map_err(|error| ParseError(format!("could not read {}: {}", filepath, error)))?;
+    if filepath.ends_with(".xml") || filepath.ends_with(".drawio") {
+        parse_xml(file)
     } else {
-        None
+        parse_text(file)
     }
 }
 
-fn parse_text(file: String) -> Vec<AutomatonData> {
+pub fn build_automaton(
+    automaton_type: AutomatonType,
+    automaton_data: Vec<AutomatonData>,
+) -> Result<Box<dyn Automaton>, ParseError> {
+    Ok(match automaton_type {
+        AutomatonType::DFA => Box::new(DFA::new(automaton_data)?),
+        AutomatonType::NFA => Box::new(NFA::new(automaton_data)?),
+        AutomatonType::PDA => Box::new(PDA::new(automaton_data)?),
+        AutomatonType::Mealy => Box::new(Mealy::new(automaton_data)?),
+        AutomatonType::Moore => Box::new(Moore::new(automaton_data)?),
+        AutomatonType::Buchi => Box::new(Buchi::new(automaton_data)?),
+        AutomatonType::TwoDFA => Box::new(TwoDFA::new(automaton_data)?),
+        AutomatonType::LBA => Box::new(LBA::new(automaton_data)?),
+        AutomatonType::Counter => Box::new(Counter::new(automaton_data)?),
+        AutomatonType::WFA => Box::new(WFA::new(automaton_data)?),
+    })
+}
+
+// Writes automaton data in the text format, states are named by their ids
+pub fn to_text(data: &[AutomatonData]) -> String {
+    let symbols = |symbols: &[char]| {
+        symbols
+            .iter()
+            .map(char::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    data.iter()
+        .map(|d| match d {
+            AutomatonData::Edge(source, target, label) => {
+                format!("q{} q{} {}", source, target, label)
+            }
+            AutomatonData::Final(id) => format!("f q{}", id),
+            AutomatonData::Start(id) => format!("s q{}", id),
+            AutomatonData::Alphabet(alphabet) => format!("a {}", symbols(alphabet)),
+            AutomatonData::StackAlphabet(alphabet) => format!("g {}", symbols(alphabet)),
+            AutomatonData::Output(id, output) => format!("o q{} {}", id, output),
+            AutomatonData::Threshold(threshold) => format!("w {}", threshold),
+            // names can't be written in place of the ids, so they are kept as comments
+            AutomatonData::Name(id, name) => format!("c q{} {}", id, name),
+        })
+        .map(|line| line + "\n")
+        .collect()
+}
+
+fn parse_text(file: String) -> Result<Vec<AutomatonData>, ParseError> {
     let mut idgen = IdGenerator::new();
-    file.lines()
-        .filter_map(|line: &str| {
+    // o and w were added later, files with states of those names still read them as states
+    let targets: HashSet<&str> = file
+        .lines()
+        .filter_map(|line| {
             let mut values = line.split_whitespace();
-            if let Some(value) = values.next() {
+            let first = values.next()?;
+            (!["c", "t", "a", "g"].contains(&first)).then_some(values.next()?)
+        })
+        .collect();
+    let mut data: Vec<_> = file
+        .lines()
+        .map(|line: &str| {
+            let mut values = line.split_whitespace();
+            let missing = |what: &str| ParseError(format!("missing {} in '{}'", what, line));
+            Ok(if let Some(value) = values.next() {
                 match value {
                     // Ignore Comments
                     "c" | "t" => None,
                     // Start State
                     "s" => Some(AutomatonData::Start(
-                        idgen.get(values.next().expect("missing start state identifier")),
+                        idgen.get(values.next().ok_or(missing("start state identifier"))?),
                     )),
                     // Final State
                     "f" => Some(AutomatonData::Final(
-                        idgen.get(values.next().expect("missing final state identifier")),
+                        idgen.get(values.next().ok_or(missing("final state identifier"))?),
                     )),
+                    // Input Alphabet
+                    "a" => Some(AutomatonData::Alphabet(parse_alphabet(
+                        &values.collect::<String>(),
+                    ))),
+                    // Output of a moore state
+                    "o" if !targets.contains("o") => Some(AutomatonData::Output(
+                        idgen.get(values.next().ok_or(missing("output state identifier"))?),
+                        values.collect::<Vec<_>>().join(" "),
+                    )),
+                    // Threshold of a weighted automaton
+                    "w" if !targets.contains("w") && values.clone().count() == 1 => {
+                        Some(AutomatonData::Threshold(
+                            parse_weight(values.next().ok_or(missing("threshold"))?)
+                                .ok_or(missing("number as threshold"))?,
+                        ))
+                    }
+                    // Stack Alphabet
+                    "g" => Some(AutomatonData::StackAlphabet(parse_alphabet(
+                        &values.collect::<String>(),
+                    ))),
                     _ => {
                         if let Some(target) = values.next() {
+                            // the label is the rest of the line, so it may contain spaces (like "a, b")
+                            let label = values.collect::<Vec<_>>().join(" ");
                             Some(AutomatonData::Edge(
                                 idgen.get(value),
                                 idgen.get(target),
-                                values.next().unwrap_or("e").to_string(),
+                                if label.is_empty() {
+                                    "e".to_string()
+                                } else {
+                                    label
+                                },
                             ))
                         } else {
                             info!("ignored pattern {line}");
@@ -62,14 +163,29 @@ fn parse_text(file: String) -> Vec<AutomatonData> {
             } else {
                 info!("empty line");
                 None
-            }
+            })
         })
-        .collect()
+        .filter_map(Result::transpose)
+        .collect::<Result<_, _>>()?;
+    // the identifiers in the file are the names of the states
+    data.extend(idgen.names());
+    Ok(data)
+}
+
+// A text box with the threshold of a weighted automaton, like "threshold = 0.5",
+// anything else starting with "threshold" is the name of a state
+fn parse_threshold(value: &str) -> Option<f64> {
+    parse_weight(
+        value
+            .strip_prefix("threshold")?
+            .trim_start()
+            .strip_prefix('=')?,
+    )
 }
 
-fn parse_xml(file: String) -> Vec<AutomatonData> {
-    let data =
-        roxmltree::Document::parse(&file).expect("XML Parsing Error (roxmltree threw an Error)");
+fn parse_xml(file: String) -> Result<Vec<AutomatonData>, ParseError> {
+    let data = roxmltree::Document::parse(&file)
+        .map_err(|error| ParseError(format!("XML Parsing Error: {}", error)))?;
     let mut idgen = IdGenerator::new();
     // labels can be either as a value directly on the edge or as a separate vertex linking to the edge
     // we extract the labels that are vertices early to be able to loop over them when needed
@@ -80,26 +196,57 @@ fn parse_xml(file: String) -> Vec<AutomatonData> {
     // Look through all nodes
     data.descendants()
         .filter(|node| node.has_attribute("edge") || node.has_attribute("vertex"))
-        .flat_map(|node| {
-            // Find final states
-            if node.has_attribute("vertex") {
-                if has_style(&node, "shape=doubleEllipse") {
-                    vec![AutomatonData::Final(idgen.get(
-                        node.attribute("id").unwrap_or_else(|| {
-                            node.parent()
-                                .expect("final vertex without id and parent")
-                                .attribute("id")
-                                .expect("final vertex without id")
-                        }),
+        .map(|node| {
+            // Find final states and state outputs
+            Ok(if node.has_attribute("vertex") {
+                let value = sanitize_label(vertex_value(&node))
+                    .concat()
+                    .replace("&Sigma;", "Σ")
+                    .replace("&Gamma;", "Γ");
+                if let Some(declaration) = value.strip_prefix("Σ") {
+                    // text box declaring the input alphabet, like "Σ = {a, b}"
+                    vec![AutomatonData::Alphabet(parse_alphabet(
+                        declaration.trim_start().trim_start_matches('='),
                     ))]
-                } else {
+                } else if let Some(declaration) = value.strip_prefix("Γ") {
+                    // text box declaring the stack alphabet
+                    vec![AutomatonData::StackAlphabet(parse_alphabet(
+                        declaration.trim_start().trim_start_matches('='),
+                    ))]
+                } else if let Some(threshold) = parse_threshold(&value) {
+                    vec![AutomatonData::Threshold(threshold)]
+                } else if has_style(&node, "edgeLabel") {
                     Vec::new()
+                } else {
+                    let id = node
+                        .attribute("id")
+                        .or_else(|| node.parent().and_then(|parent| parent.attribute("id")))
+                        .ok_or(ParseError::new("vertex without id"))?;
+                    let mut data = Vec::new();
+                    if has_style(&node, "shape=doubleEllipse") {
+                        data.push(AutomatonData::Final(idgen.get(id)));
+                    }
+                    if !value.is_empty() {
+                        // the output of a moore state is written behind its name, like "q0/1"
+                        let name = match value.split_once('/') {
+                            Some((name, output)) => {
+                                let output = output.trim().to_string();
+                                data.push(AutomatonData::Output(idgen.get(id), output));
+                                name
+                            }
+                            None => &value,
+                        };
+                        data.push(AutomatonData::Name(idgen.get(id), name.trim().to_string()));
+                    }
+                    data
                 }
             } else {
                 // Parse edges
                 assert!(node.has_attribute("edge"));
                 if node.has_attribute("source") && node.has_attribute("target") {
-                    let id = node.attribute("id").expect("label without id");
+                    let id = node
+                        .attribute("id")
+                        .ok_or(ParseError::new("edge without id"))?;
                     // check if edge has label as value
                     let mut label = node.attribute("value").unwrap_or("");
                     if label.is_empty() {
@@ -108,7 +255,7 @@ fn parse_xml(file: String) -> Vec<AutomatonData> {
                             label = ulabel;
                         } else {
                             warn!("Ignoring Edge Without Label");
-                            return vec![];
+                            return Ok(vec![]);
                         }
                     }
                     sanitize_label(label) // might split label up into multiple lines
@@ -155,19 +302,37 @@ fn parse_xml(file: String) -> Vec<AutomatonData> {
                     warn!("Ignoring free floating edge");
                     Vec::new()
                 }
-            }
+            })
+        })
+        .collect::<Result<Vec<_>, ParseError>>()
+        .map(|data| drop_free_text(data.into_iter().flatten().collect()))
+}
+
+// Text boxes like an exercise title are vertices too, only those an edge or a marking refers to are states
+fn drop_free_text(data: Vec<AutomatonData>) -> Vec<AutomatonData> {
+    let states: HashSet<u32> = data
+        .iter()
+        .flat_map(|d| match d {
+            AutomatonData::Edge(source, target, _) => vec![*source, *target],
+            AutomatonData::Start(id) | AutomatonData::Final(id) => vec![*id],
+            _ => Vec::new(),
+        })
+        .collect();
+    data.into_iter()
+        .filter(|d| match d {
+            AutomatonData::Name(id, _) | AutomatonData::Output(id, _) => states.contains(id),
+            _ => true,
         })
         .collect()
 }
 
 // Looks for a label which parent is the given id of an Edge
-// Panics if a label doesnt have a parent
 // Returns None if it cant find a label
 fn find_related_label<'a>(id: &'a str, labels: &'a Vec<Node<'_, '_>>) -> Option<&'a str> {
     labels
         .iter()
-        .find(|label| label.attribute("parent").expect("label without parent") == id)
-        .map(|label| label.attribute("value").expect("label without value"))
+        .find(|label| label.attribute("parent") == Some(id))
+        .and_then(|label| label.attribute("value"))
 }
 
 // Helper function to check if the style of a Node contains a str
@@ -178,18 +343,37 @@ fn has_style(node: &Node<'_, '_>, style: &str) -> bool {
         .contains(&style.to_lowercase())
 }
 
+// The value of a vertex, which is on the wrapping object if the vertex has custom properties
+fn vertex_value<'a>(node: &Node<'a, '_>) -> &'a str {
+    node.attribute("value")
+        .or_else(|| node.parent().and_then(|parent| parent.attribute("label")))
+        .unwrap_or("")
+}
+
 // Split the weird multiline label syntax of drawio and remove the tags
+// div and br tags start new lines, every other tag is dropped
 fn sanitize_label(label: &str) -> Vec<String> {
-    let label = label.replace("<br>", "");
-    label
-        .split("div")
-        .map(|l| {
-            l.replace("<", "")
-                .replace(">", "")
-                .replace("/", "")
-                .trim()
-                .to_owned()
-        })
+    let mut text = String::new();
+    let mut rest = label;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim_start_matches('/').trim();
+        if tag.starts_with("div") || tag.starts_with("br") {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+        .lines()
+        .map(|l| l.trim().to_owned())
         .filter(|l| !l.is_empty())
         .collect()
 }
@@ -212,6 +396,20 @@ impl IdGenerator {
         }
     }
 
+    // The identifiers that were given out, ordered by their id
+    fn names(&self) -> Vec<AutomatonData> {
+        let mut names: Vec<_> = self
+            .id_map
+            .iter()
+            .map(|(name, id)| AutomatonData::Name(*id, name.clone()))
+            .collect();
+        names.sort_by_key(|name| match name {
+            AutomatonData::Name(id, _) => *id,
+            _ => 0,
+        });
+        names
+    }
+
     fn new() -> Self {
         IdGenerator {
             id_map: HashMap::new(),
@@ -219,3 +417,23 @@ impl IdGenerator {
         }
     }
 }
+
+#[test]
+fn test_sanitize_label() {
+    assert_eq!(sanitize_label("a"), vec!["a"]);
+    assert_eq!(sanitize_label("a<div>b</div>"), vec!["a", "b"]);
+    assert_eq!(sanitize_label("a/1<br>b/0"), vec!["a/1", "b/0"]);
+    assert_eq!(
+        sanitize_label("<font style=\"font-size: 15px;\">z<sub>0</sub></font>"),
+        vec!["z0"]
+    );
+}
+
+#[test]
+fn test_parse_threshold() {
+    assert_eq!(parse_threshold("threshold = 0.5"), Some(0.5));
+    assert_eq!(parse_threshold("threshold=1/4"), Some(0.25));
+    // states whose names start with threshold
+    assert_eq!(parse_threshold("threshold1"), None);
+    assert_eq!(parse_threshold("threshold = high"), None);
+}
@@ -0,0 +1,411 @@
+use std::collections::{HashMap, VecDeque};
+
+// Conversions with more states give up, so subset constructions that blow up can't hang the process
+// (comparisons fall back to generated words then)
+pub const MAX_STATES: usize = 100_000;
+
+// A complete DFA over a fixed alphabet with states numbered from 0,
+// finite automatons can be converted into this to analyse their languages exactly
+
+pub struct RegularDfa {
+    pub alphabet: Vec<char>,
+    // transitions[state][i] is the state reached by reading alphabet[i]
+    pub transitions: Vec<Vec<usize>>,
+    pub accepting: Vec<bool>,
+    pub start: usize,
+}
+
+impl RegularDfa {
+    // Builds the reachable part of a DFA from a start key and a step function
+    // step returns the key reached by reading a symbol from the given key
+    // None if more than max_states states are reachable
+    pub fn explore<K, S, A>(
+        alphabet: &[char],
+        start: K,
+        step: S,
+        accepting: A,
+        max_states: usize,
+    ) -> Option<RegularDfa>
+    where
+        K: Clone + Eq + std::hash::Hash,
+        S: Fn(&K, char) -> K,
+        A: Fn(&K) -> bool,
+    {
+        let mut ids: HashMap<K, usize> = HashMap::new();
+        let mut keys = vec![start.clone()];
+        ids.insert(start, 0);
+        let mut transitions = Vec::new();
+        let mut queue = VecDeque::from([0]);
+        // states are numbered in the order they are found, so they are explored in order of their ids
+        while let Some(id) = queue.pop_front() {
+            if keys.len() > max_states {
+                return None;
+            }
+            let row = alphabet
+                .iter()
+                .map(|&symbol| {
+                    let next = step(&keys[id], symbol);
+                    *ids.entry(next.clone()).or_insert_with(|| {
+                        keys.push(next);
+                        queue.push_back(keys.len() - 1);
+                        keys.len() - 1
+                    })
+                })
+                .collect();
+            transitions.push(row);
+        }
+        if keys.len() > max_states {
+            return None;
+        }
+        Some(RegularDfa {
+            alphabet: alphabet.to_vec(),
+            accepting: keys.iter().map(accepting).collect(),
+            transitions,
+            start: 0,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transitions.is_empty()
+    }
+
+    // The reachable states of the product automaton, accepting if both agree on the word
+    pub fn agreement(&self, other: &RegularDfa) -> Option<RegularDfa> {
+        self.product(other, |a, b| a == b)
+    }
+
+    // The reachable states of the product automaton,
+    // accept decides from the acceptance of both automatons if the product accepts
+    // None if it has more than MAX_STATES states
+    pub fn product(
+        &self,
+        other: &RegularDfa,
+        accept: impl Fn(bool, bool) -> bool,
+    ) -> Option<RegularDfa> {
+        assert_eq!(
+            self.alphabet, other.alphabet,
+            "product needs equal alphabets"
+        );
+        RegularDfa::explore(
+            &self.alphabet,
+            (self.start, other.start),
+            |&(a, b), symbol| {
+                let i = self.symbol_index(symbol);
+                (self.transitions[a][i], other.transitions[b][i])
+            },
+            |&(a, b)| accept(self.accepting[a], other.accepting[b]),
+            MAX_STATES,
+        )
+    }
+
+    // The dfa is complete, so swapping accepting and rejecting states accepts exactly the other words
+    pub fn complement(&self) -> RegularDfa {
+        RegularDfa {
+            alphabet: self.alphabet.clone(),
+            transitions: self.transitions.clone(),
+            accepting: self.accepting.iter().map(|accepting| !accepting).collect(),
+            start: self.start,
+        }
+    }
+
+    // Fraction of words of each length 0..=max_length that are accepted
+    // counts are kept as floats, only their ratio matters and they would overflow quickly
+    pub fn accepted_fractions(&self, max_length: usize) -> Vec<f64> {
+        let mut counts = vec![0.0; self.len()];
+        counts[self.start] = 1.0;
+        let mut fractions = Vec::with_capacity(max_length + 1);
+        for l in 0..=max_length {
+            let total: f64 = counts.iter().sum();
+            let accepted: f64 = counts
+                .iter()
+                .zip(&self.accepting)
+                .filter(|(_, &accepting)| accepting)
+                .map(|(count, _)| count)
+                .sum();
+            fractions.push(if total > 0.0 { accepted / total } else { 1.0 });
+            if l == max_length || self.alphabet.is_empty() {
+                break;
+            }
+            let mut next = vec![0.0; self.len()];
+            for (state, count) in counts.iter().enumerate() {
+                for &target in &self.transitions[state] {
+                    next[target] += count;
+                }
+            }
+            // normalize, so the numbers stay in range for long words and big alphabets
+            let sum: f64 = next.iter().sum();
+            counts = next.into_iter().map(|count| count / sum).collect();
+        }
+        fractions
+    }
+
+    pub fn accepts(&self, word: &str) -> bool {
+        let end = word.chars().try_fold(self.start, |state, symbol| {
+            let i = self.alphabet.iter().position(|&s| s == symbol)?;
+            Some(self.transitions[state][i])
+        });
+        end.is_some_and(|state| self.accepting[state])
+    }
+
+    // Number of accepted words of each length 0..=max_length (saturating)
+    pub fn accepted_counts(&self, max_length: usize) -> Vec<u128> {
+        let mut counts = vec![0u128; self.len()];
+        counts[self.start] = 1;
+        let mut accepted = Vec::with_capacity(max_length + 1);
+        for _ in 0..=max_length {
+            accepted.push(
+                counts
+                    .iter()
+                    .zip(&self.accepting)
+                    .filter(|(_, &accepting)| accepting)
+                    .fold(0u128, |sum, (count, _)| sum.saturating_add(*count)),
+            );
+            let mut next = vec![0u128; self.len()];
+            for (state, count) in counts.iter().enumerate() {
+                for &target in &self.transitions[state] {
+                    next[target] = next[target].saturating_add(*count);
+                }
+            }
+            counts = next;
+        }
+        accepted
+    }
+
+    // The shortest accepted word (the first in alphabetical order), None if the language is empty
+    pub fn shortest_word(&self) -> Option<String> {
+        let mut words: Vec<Option<String>> = vec![None; self.len()];
+        words[self.start] = Some(String::new());
+        let mut queue = VecDeque::from([self.start]);
+        while let Some(state) = queue.pop_front() {
+            if self.accepting[state] {
+                return words[state].clone();
+            }
+            for (symbol, &target) in self.alphabet.iter().zip(&self.transitions[state]) {
+                if words[target].is_none() {
+                    words[target] = words[state].as_ref().map(|word| format!("{word}{symbol}"));
+                    queue.push_back(target);
+                }
+            }
+        }
+        None
+    }
+
+    // States from which an accepting state can be reached, found backwards from the accepting ones
+    fn productive(&self) -> Vec<bool> {
+        let mut sources = vec![Vec::new(); self.len()];
+        for (state, targets) in self.transitions.iter().enumerate() {
+            for &target in targets {
+                sources[target].push(state);
+            }
+        }
+        let mut productive = self.accepting.clone();
+        let mut queue: VecDeque<usize> = (0..self.len()).filter(|&s| productive[s]).collect();
+        while let Some(state) = queue.pop_front() {
+            for &source in &sources[state] {
+                if !productive[source] {
+                    productive[source] = true;
+                    queue.push_back(source);
+                }
+            }
+        }
+        productive
+    }
+
+    // All states are reachable, so the language is infinite exactly if
+    // there is a cycle through states that can still reach an accepting state
+    pub fn is_finite(&self) -> bool {
+        let productive = self.productive();
+        if !productive[self.start] {
+            return true;
+        }
+        // 0 = unvisited, 1 = on the current path, 2 = done
+        let mut marks = vec![0u8; self.len()];
+        marks[self.start] = 1;
+        // the path of the depth first search, with the index of the next transition to follow
+        // kept on the heap, as subset constructions can have long paths
+        let mut path = vec![(self.start, 0)];
+        while let Some((state, next)) = path.last_mut() {
+            let Some(&target) = self.transitions[*state].get(*next) else {
+                marks[*state] = 2;
+                path.pop();
+                continue;
+            };
+            *next += 1;
+            if !productive[target] {
+                continue;
+            }
+            match marks[target] {
+                1 => return false,
+                0 => {
+                    marks[target] = 1;
+                    path.push((target, 0));
+                }
+                _ => {}
+            }
+        }
+        true
+    }
+
+    // All accepted words sorted by length and alphabetically, None if the language is infinite
+    pub fn words(&self) -> Option<Vec<String>> {
+        if !self.is_finite() {
+            return None;
+        }
+        let productive = self.productive();
+        let mut words = Vec::new();
+        let mut current = vec![(self.start, String::new())];
+        // a finite language has no word longer than the number of states
+        while !current.is_empty() {
+            let mut next = Vec::new();
+            for (state, word) in current {
+                if self.accepting[state] {
+                    words.push(word.clone());
+                }
+                for (symbol, &target) in self.alphabet.iter().zip(&self.transitions[state]) {
+                    if productive[target] {
+                        next.push((target, format!("{word}{symbol}")));
+                    }
+                }
+            }
+            current = next;
+        }
+        Some(words)
+    }
+
+    pub fn is_universal(&self) -> bool {
+        self.complement().shortest_word().is_none()
+    }
+
+    // The equivalent dfa with the fewest states, states that can't be told apart by any word are merged
+    pub fn minimize(&self) -> RegularDfa {
+        // start with accepting and rejecting states and split classes until they are stable
+        let mut classes: Vec<usize> = self.accepting.iter().map(|&a| a as usize).collect();
+        let mut count = 0;
+        loop {
+            let mut ids: HashMap<(usize, Vec<usize>), usize> = HashMap::new();
+            let refined: Vec<usize> = (0..self.len())
+                .map(|state| {
+                    let targets = self.transitions[state].iter().map(|&t| classes[t]);
+                    let next = ids.len();
+                    *ids.entry((classes[state], targets.collect()))
+                        .or_insert(next)
+                })
+                .collect();
+            classes = refined;
+            if ids.len() == count {
+                break;
+            }
+            count = ids.len();
+        }
+        let mut representatives = vec![0; count];
+        for (state, &class) in classes.iter().enumerate() {
+            representatives[class] = state;
+        }
+        RegularDfa::explore(
+            &self.alphabet,
+            classes[self.start],
+            |&class, symbol| {
+                classes[self.transitions[representatives[class]][self.symbol_index(symbol)]]
+            },
+            |&class| self.accepting[representatives[class]],
+            count,
+        )
+        .expect("there is one state per class")
+    }
+
+    fn symbol_index(&self, symbol: char) -> usize {
+        self.alphabet
+            .iter()
+            .position(|&s| s == symbol)
+            .expect("symbol not in alphabet")
+    }
+}
+
+#[test]
+fn test_fractions() {
+    // accepts words with an even number of a's over {a, b}
+    let even = RegularDfa {
+        alphabet: vec!['a', 'b'],
+        transitions: vec![vec![1, 0], vec![0, 1]],
+        accepting: vec![true, false],
+        start: 0,
+    };
+    assert_eq!(even.accepted_fractions(2), vec![1.0, 0.5, 0.5]);
+    // accepts only the empty word
+    let empty = RegularDfa {
+        alphabet: vec!['a', 'b'],
+        transitions: vec![vec![1, 1], vec![1, 1]],
+        accepting: vec![true, false],
+        start: 0,
+    };
+    assert_eq!(
+        even.agreement(&empty).unwrap().accepted_fractions(2),
+        vec![1.0, 0.5, 0.5]
+    );
+}
+
+#[test]
+fn test_properties() {
+    // accepts words with an even number of a's over {a, b}
+    let even = RegularDfa {
+        alphabet: vec!['a', 'b'],
+        transitions: vec![vec![1, 0], vec![0, 1]],
+        accepting: vec![true, false],
+        start: 0,
+    };
+    assert_eq!(even.accepted_counts(3), vec![1, 1, 2, 4]);
+    assert_eq!(even.shortest_word().as_deref(), Some(""));
+    assert!(!even.is_finite());
+    assert!(!even.is_universal());
+    assert_eq!(even.complement().shortest_word().as_deref(), Some("a"));
+    // accepts a and ab
+    let finite = RegularDfa {
+        alphabet: vec!['a', 'b'],
+        transitions: vec![vec![1, 3], vec![3, 2], vec![3, 3], vec![3, 3]],
+        accepting: vec![false, true, true, false],
+        start: 0,
+    };
+    assert_eq!(
+        finite.words(),
+        Some(vec!["a".to_string(), "ab".to_string()])
+    );
+    assert!(!finite.complement().is_finite());
+    assert!(finite
+        .product(&finite.complement(), |a, b| a || b)
+        .unwrap()
+        .is_universal());
+}
+
+#[test]
+fn test_minimize() {
+    // accepts words with an even number of a's, with two copies of every state
+    let doubled = RegularDfa {
+        alphabet: vec!['a', 'b'],
+        transitions: vec![vec![1, 2], vec![2, 3], vec![3, 0], vec![0, 1]],
+        accepting: vec![true, false, true, false],
+        start: 0,
+    };
+    let minimal = doubled.minimize();
+    assert_eq!(minimal.len(), 2);
+    assert_eq!(minimal.accepted_counts(4), doubled.accepted_counts(4));
+}
+
+#[test]
+fn test_long_paths() {
+    // accepts only the word of n a's, a path too long for a recursive search
+    let n = 200_000;
+    let chain = RegularDfa {
+        alphabet: vec!['a'],
+        transitions: (0..=n + 1)
+            .map(|state| vec![(state + 1).min(n + 1)])
+            .collect(),
+        accepting: (0..=n + 1).map(|state| state == n).collect(),
+        start: 0,
+    };
+    assert!(chain.is_finite());
+    assert!(!chain.complement().is_finite());
+}
@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use super::automaton::*;
+use super::budget::Budget;
+use super::evaluation::{counterexample, GenerationConfig, Witness};
+use super::lint::{lint, trap_states, Lint};
+use super::utils::{parse_output, parse_symbols, parse_weight, show_word, LabelSymbol};
+
+// A requirement of a grading rubric, each one gives its points if it holds
+#[derive(Clone, Debug, PartialEq)]
+pub enum Criterion {
+    // accepts the same words as the reference (or the relation that is configured)
+    Equivalent,
+    // no epsilon transitions and at most one transition per state and symbol
+    Deterministic,
+    // at most this many states more than the minimal dfa of the reference
+    States(usize),
+    // every state can be reached from the start state
+    Reachable,
+    Accepts(Vec<String>),
+    Rejects(Vec<String>),
+}
+
+impl fmt::Display for Criterion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let words = |words: &[String]| {
+            words
+                .iter()
+                .map(|word| show_word(word))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            Criterion::Equivalent => write!(f, "equivalent to the reference"),
+            Criterion::Deterministic => write!(f, "deterministic"),
+            Criterion::States(0) => write!(f, "minimal number of states"),
+            Criterion::States(k) => write!(f, "at most {} states more than minimal", k),
+            Criterion::Reachable => write!(f, "no unreachable states"),
+            Criterion::Accepts(accepted) => write!(f, "accepts {}", words(accepted)),
+            Criterion::Rejects(rejected) => write!(f, "rejects {}", words(rejected)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rubric {
+    pub criteria: Vec<(Criterion, f64)>,
+}
+
+impl Rubric {
+    // Parses lines of the format "criterion points arguments", e.g. "equivalent 6", "states 1 2"
+    // or "accept 1 a bba", lines starting with # are comments
+    pub fn parse(text: &str) -> Result<Rubric, ParseError> {
+        let mut criteria = Vec::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |what: &str| ParseError(format!("{} in '{}'", what, line));
+            let mut items = line.split_whitespace();
+            let name = items.next().unwrap_or_default();
+            let points = items
+                .next()
+                .and_then(parse_weight)
+                .ok_or(invalid("no point value given"))?;
+            let arguments: Vec<_> = items.collect();
+            let criterion = match (name, &arguments[..]) {
+                ("equivalent", []) => Criterion::Equivalent,
+                ("deterministic", []) => Criterion::Deterministic,
+                ("states", []) => Criterion::States(0),
+                ("states", [k]) => {
+                    Criterion::States(k.parse().map_err(|_| invalid("invalid number of states"))?)
+                }
+                ("reachable", []) => Criterion::Reachable,
+                ("accept", [_, ..]) => {
+                    Criterion::Accepts(arguments.iter().map(|word| parse_output(word)).collect())
+                }
+                ("reject", [_, ..]) => {
+                    Criterion::Rejects(arguments.iter().map(|word| parse_output(word)).collect())
+                }
+                _ => return Err(invalid("unknown criterion or wrong arguments")),
+            };
+            criteria.push((criterion, points));
+        }
+        Ok(Rubric { criteria })
+    }
+}
+
+// The points given for one criterion, with the reason if some were deducted
+#[derive(Clone, Debug, PartialEq)]
+pub struct Score {
+    pub criterion: Criterion,
+    pub points: f64,
+    pub max: f64,
+    pub deduction: Option<String>,
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{} {}", self.points, self.max, self.criterion)?;
+        if let Some(deduction) = &self.deduction {
+            write!(f, ": {}", deduction)?;
+        }
+        Ok(())
+    }
+}
+
+// Checks every criterion of the rubric, data is what the automaton was built from
+pub fn grade(
+    rubric: &Rubric,
+    data: &[AutomatonData],
+    automaton_type: &AutomatonType,
+    automaton: &dyn Automaton,
+    reference: &dyn Automaton,
+    config: &GenerationConfig,
+    budget: &Budget,
+) -> Vec<Score> {
+    rubric
+        .criteria
+        .iter()
+        .map(|(criterion, max)| {
+            let deduction = match criterion {
+                Criterion::Equivalent => counterexample(automaton, reference, config, budget).map(
+                    |witness| match witness {
+                        Witness::Differs(word) => {
+                            format!("the automatons differ on '{}'", show_word(&word))
+                        }
+                        Witness::Exceeded(word, reason) => {
+                            format!(
+                                "{} on '{}', equivalence not shown",
+                                reason,
+                                show_word(&word)
+                            )
+                        }
+                    },
+                ),
+                Criterion::Deterministic => nondeterminism(data, automaton_type),
+                Criterion::States(k) => too_many_states(data, automaton, reference, config, *k),
+                Criterion::Reachable => unreachable(data),
+                Criterion::Accepts(words) => wrong_words(automaton, words, true, budget),
+                Criterion::Rejects(words) => wrong_words(automaton, words, false, budget),
+            };
+            Score {
+                criterion: criterion.clone(),
+                points: if deduction.is_some() { 0.0 } else { *max },
+                max: *max,
+                deduction,
+            }
+        })
+        .collect()
+}
+
+// Looks at the drawn transitions, a dfa would silently keep only one of two transitions for a symbol
+fn nondeterminism(data: &[AutomatonData], automaton_type: &AutomatonType) -> Option<String> {
+    // other types have more than the read symbol in their labels
+    if !matches!(
+        automaton_type,
+        AutomatonType::DFA | AutomatonType::NFA | AutomatonType::Moore | AutomatonType::Buchi
+    ) {
+        return Some("determinism is only checked for automatons labeled with symbols".to_string());
+    }
+    let mut targets = HashMap::new();
+    for d in data {
+        let AutomatonData::Edge(source, target, label) = d else {
+            continue;
+        };
+        // the automaton was built from the data, so the labels are valid
+        for symbol in parse_symbols(label).unwrap_or_default() {
+            // None stands for the rest of the alphabet
+            let symbol = match symbol {
+                LabelSymbol::Symbol(c) => Some(c),
+                LabelSymbol::Epsilon => {
+                    return Some(format!("state {} has an epsilon transition", source))
+                }
+                LabelSymbol::Else | LabelSymbol::Any => None,
+            };
+            if *targets.entry((*source, symbol)).or_insert(*target) != *target {
+                return Some(match symbol {
+                    Some(c) => format!("state {} has more than one transition for '{}'", source, c),
+                    None => format!("state {} has more than one else transition", source),
+                });
+            }
+        }
+    }
+    None
+}
+
+fn too_many_states(
+    data: &[AutomatonData],
+    automaton: &dyn Automaton,
+    reference: &dyn Automaton,
+    config: &GenerationConfig,
+    k: usize,
+) -> Option<String> {
+    let alphabet = config.shared_alphabet(automaton, reference);
+    let Some(minimal) = reference.to_regular(&alphabet).map(|dfa| dfa.minimize()) else {
+        return Some(
+            "the minimal number of states is only known for finite automatons".to_string(),
+        );
+    };
+    // a trap state is usually left out, so it isn't counted on either side
+    let traps = (0..minimal.len())
+        .filter(|&state| {
+            !minimal.accepting[state]
+                && minimal.transitions[state]
+                    .iter()
+                    .all(|&target| target == state)
+        })
+        .count();
+    let allowed = minimal.len() - traps + k;
+    let drawn_traps = trap_states(data);
+    let states = automaton
+        .states()
+        .iter()
+        .filter(|state| !drawn_traps.contains(state))
+        .count();
+    (states > allowed).then(|| format!("{} states, at most {} are needed", states, allowed))
+}
+
+fn unreachable(data: &[AutomatonData]) -> Option<String> {
+    let states: Vec<_> = lint(data)
+        .into_iter()
+        .filter_map(|lint| match lint {
+            Lint::Unreachable(state) => Some(state.to_string()),
+            _ => None,
+        })
+        .collect();
+    (!states.is_empty()).then(|| format!("can't reach {}", states.join(", ")))
+}
+
+fn wrong_words(
+    automaton: &dyn Automaton,
+    words: &[String],
+    accept: bool,
+    budget: &Budget,
+) -> Option<String> {
+    let wrong: Vec<_> = words
+        .iter()
+        .filter(|word| automaton.accepts_within(word, &mut budget.limit()) != Ok(accept))
+        .map(|word| format!("'{}'", show_word(word)))
+        .collect();
+    let verb = if accept { "rejects" } else { "accepts" };
+    (!wrong.is_empty()).then(|| format!("{} {}", verb, wrong.join(", ")))
+}
@@ -0,0 +1,178 @@
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+use super::evaluation::{GenerationConfig, LengthDistribution};
+
+// How many words per verdict are remembered for boundary biased sampling
+const POOL_SIZE: usize = 1000;
+
+// Draws random words for the generated comparison
+// The length is drawn first, then every symbol independently,
+// so all words of one length are equally likely (and no alphabet.len().pow(len) is needed)
+pub struct WordSampler<'a> {
+    alphabet: &'a [char],
+    rng: ChaCha8Rng,
+    // a separate stream for the pools, so remembering words doesn't change which words are drawn
+    pool_rng: ChaCha8Rng,
+    lengths: Vec<usize>,
+    weights: Option<WeightedIndex<f64>>,
+    // only used if the config wasn't validated and has no valid lengths
+    min_length: usize,
+    boundary_bias: f64,
+    // words the reference accepted and rejected, used to generate words near the boundary
+    accepted: Vec<String>,
+    rejected: Vec<String>,
+    accepted_count: usize,
+    rejected_count: usize,
+}
+
+impl<'a> WordSampler<'a> {
+    pub fn new(alphabet: &'a [char], config: &GenerationConfig) -> Self {
+        let (lengths, weights): (Vec<usize>, Vec<f64>) = if config.length_weights.is_empty() {
+            let lengths = config.random_lengths.clone();
+            let n = lengths.len();
+            lengths
+                .enumerate()
+                .map(|(i, l)| {
+                    let weight = match config.distribution {
+                        LengthDistribution::Uniform => 1,
+                        LengthDistribution::Short => n - i,
+                        LengthDistribution::Long => i + 1,
+                    };
+                    (l, weight as f64)
+                })
+                .unzip()
+        } else {
+            config.length_weights.iter().copied().enumerate().unzip()
+        };
+        let mut pool_rng = ChaCha8Rng::seed_from_u64(config.seed);
+        pool_rng.set_stream(1);
+        WordSampler {
+            alphabet,
+            rng: ChaCha8Rng::seed_from_u64(config.seed),
+            pool_rng,
+            weights: WeightedIndex::new(&weights).ok(),
+            lengths,
+            min_length: config.random_lengths.start,
+            boundary_bias: config.boundary_bias,
+            accepted: Vec::new(),
+            rejected: Vec::new(),
+            accepted_count: 0,
+            rejected_count: 0,
+        }
+    }
+
+    pub fn length(&mut self) -> usize {
+        self.weights
+            .as_ref()
+            .map(|weights| self.lengths[weights.sample(&mut self.rng)])
+            .unwrap_or(self.min_length)
+    }
+
+    pub fn sample(&mut self) -> String {
+        if self.boundary_bias > 0.0 && self.rng.gen_bool(self.boundary_bias.min(1.0)) {
+            if let Some(word) = self.near_boundary() {
+                return word;
+            }
+        }
+        let len = self.length();
+        self.uniform_word(len)
+    }
+
+    pub fn uniform_word(&mut self, len: usize) -> String {
+        if self.alphabet.is_empty() {
+            return String::new();
+        }
+        (0..len)
+            .map(|_| *self.alphabet.choose(&mut self.rng).unwrap())
+            .collect()
+    }
+
+    // Turns a word into an infinite word u(v) by splitting it at a random position
+    // An empty word is only possible over an empty alphabet, where there are no infinite words
+    pub fn lasso(&mut self, word: &str) -> Option<String> {
+        let mut chars: Vec<char> = word.chars().collect();
+        if chars.is_empty() {
+            // v may not be empty
+            chars = self.uniform_word(1).chars().collect();
+            if chars.is_empty() {
+                return None;
+            }
+        }
+        let split = self.rng.gen_range(0..chars.len());
+        let u: String = chars[..split].iter().collect();
+        let v: String = chars[split..].iter().collect();
+        Some(format!("{}({})", u, v))
+    }
+
+    // Tell the sampler how the reference decided on a sampled word
+    pub fn feedback(&mut self, word: String, accepted: bool) {
+        let (pool, count) = if accepted {
+            (&mut self.accepted, &mut self.accepted_count)
+        } else {
+            (&mut self.rejected, &mut self.rejected_count)
+        };
+        *count += 1;
+        if pool.len() < POOL_SIZE {
+            pool.push(word);
+        } else {
+            // keep a uniform sample of all seen words (reservoir sampling)
+            let i = self.pool_rng.gen_range(0..*count);
+            if i < POOL_SIZE {
+                pool[i] = word;
+            }
+        }
+    }
+
+    // Changes a single symbol of a word with the rarer verdict,
+    // as words that differ by one edit from those are likely to be on the other side of the boundary
+    fn near_boundary(&mut self) -> Option<String> {
+        let pool = if self.accepted_count <= self.rejected_count && !self.accepted.is_empty() {
+            &self.accepted
+        } else if !self.rejected.is_empty() {
+            &self.rejected
+        } else {
+            &self.accepted
+        };
+        let mut word: Vec<char> = pool.choose(&mut self.rng)?.chars().collect();
+        let symbol = *self.alphabet.choose(&mut self.rng)?;
+        match self.rng.gen_range(0..3) {
+            // substitute
+            0 if !word.is_empty() => {
+                let i = self.rng.gen_range(0..word.len());
+                word[i] = symbol;
+            }
+            // delete
+            1 if !word.is_empty() => {
+                let i = self.rng.gen_range(0..word.len());
+                word.remove(i);
+            }
+            // insert
+            _ => {
+                let i = self.rng.gen_range(0..=word.len());
+                word.insert(i, symbol);
+            }
+        }
+        Some(word.into_iter().collect())
+    }
+}
+
+#[test]
+fn test_sampler_lengths() {
+    let alphabet = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j'];
+    let config = GenerationConfig {
+        length_weights: vec![0.0, 0.0, 0.0, 1.0],
+        ..GenerationConfig::default()
+    };
+    let mut sampler = WordSampler::new(&alphabet, &config);
+    assert!((0..100).all(|_| sampler.sample().chars().count() == 3));
+
+    // long words over a large alphabet used to overflow
+    let config = GenerationConfig {
+        random_lengths: 40..41,
+        ..GenerationConfig::default()
+    };
+    let mut sampler = WordSampler::new(&alphabet, &config);
+    assert_eq!(sampler.sample().chars().count(), 40);
+}
@@ -1,6 +1,10 @@
-use log::{error, info, warn};
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+use std::thread;
 
-use super::automaton::VertexId;
+use log::warn;
+
+use super::automaton::{ParseError, VertexId};
 
 pub fn format_states(states: &[VertexId]) -> String {
     states
@@ -10,13 +14,242 @@ pub fn format_states(states: &[VertexId]) -> String {
         .unwrap_or("None".to_string())
 }
 
+// Sorted states without duplicates
+pub fn sorted_states(states: impl IntoIterator<Item = VertexId>) -> Vec<VertexId> {
+    let mut states: Vec<VertexId> = states.into_iter().collect();
+    states.sort();
+    states.dedup();
+    states
+}
+
+// Sorted alphabet without epsilon, so words are always generated in the same order
+pub fn sorted_alphabet(symbols: HashSet<char>) -> Vec<char> {
+    let mut alphabet: Vec<char> = symbols.into_iter().filter(|&c| c != ' ').collect();
+    alphabet.sort();
+    alphabet
+}
+
+// The declared alphabet if there is one, otherwise the symbols used on edges
+// warns about used symbols that were not declared
+pub fn declared_alphabet(
+    declared: Option<Vec<char>>,
+    used: HashSet<char>,
+    kind: &str,
+) -> Vec<char> {
+    if let Some(declared) = declared {
+        let declared = sorted_alphabet(declared.into_iter().collect());
+        sorted_alphabet(used)
+            .into_iter()
+            .filter(|symbol| !declared.contains(symbol))
+            .for_each(|symbol| warn!("'{}' is used but not part of the declared {}", symbol, kind));
+        declared
+    } else {
+        sorted_alphabet(used)
+    }
+}
+
+// Parses the symbols of an alphabet declaration like "a b", "a,b" or "{a, b}"
+pub fn parse_alphabet(declaration: &str) -> Vec<char> {
+    declaration
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, ',' | '{' | '}'))
+        .collect()
+}
+
+// Sorted union of alphabets
+pub fn union_alphabet(alphabets: &[&[char]]) -> Vec<char> {
+    sorted_alphabet(alphabets.iter().flat_map(|a| a.iter().copied()).collect())
+}
+
 pub fn parse_char(input: &str) -> char {
-    let out = input.trim().parse().unwrap_or('e');
-    if out == 'e' {
-        info!("Parsing {} as epsilon", input);
-        ' '
+    match named_symbol(input.trim()) {
+        Some(LabelSymbol::Symbol(c)) => c,
+        Some(LabelSymbol::Epsilon) => ' ',
+        _ => {
+            warn!("Parsing {} as epsilon", input);
+            ' '
+        }
+    }
+}
+
+// One part of a DFA or NFA label
+#[derive(Debug, PartialEq, Eq)]
+pub enum LabelSymbol {
+    Symbol(char),
+    Epsilon,
+    // every symbol of the alphabet that has no other transition from the same state
+    Else,
+    // every symbol of the alphabet
+    Any,
+}
+
+// Single chars and the names that can be used for symbols
+fn named_symbol(name: &str) -> Option<LabelSymbol> {
+    match name {
+        "" | "e" | "ε" | "λ" | "eps" | "epsilon" | "lambda" => Some(LabelSymbol::Epsilon),
+        "else" | "other" => Some(LabelSymbol::Else),
+        "Σ" | "sigma" | "any" => Some(LabelSymbol::Any),
+        "comma" => Some(LabelSymbol::Symbol(',')),
+        "dash" => Some(LabelSymbol::Symbol('-')),
+        _ => {
+            let chars: Vec<char> = name.chars().collect();
+            match chars[..] {
+                [c] => Some(LabelSymbol::Symbol(c)),
+                // quotes keep a symbol from being read as a name, like 'e'
+                ['\'', c, '\''] => Some(LabelSymbol::Symbol(c)),
+                _ => None,
+            }
+        }
+    }
+}
+
+// The label that parse_symbols reads as the given symbol
+pub fn symbol_label(symbol: char) -> String {
+    match symbol {
+        ' ' => "ε".to_string(),
+        ',' => "comma".to_string(),
+        '-' => "dash".to_string(),
+        _ => match named_symbol(&symbol.to_string()) {
+            Some(LabelSymbol::Symbol(_)) => symbol.to_string(),
+            _ => format!("'{}'", symbol),
+        },
+    }
+}
+
+// Parses the output of a transducer, where epsilon names stand for the empty output
+pub fn parse_output(output: &str) -> String {
+    let output = output.trim();
+    match named_symbol(output) {
+        Some(LabelSymbol::Epsilon) => String::new(),
+        _ => output.to_string(),
+    }
+}
+
+// Shows the empty word as ε
+pub fn show_word(word: &str) -> &str {
+    if word.is_empty() {
+        "ε"
+    } else {
+        word
+    }
+}
+
+// Parses a weight like "0.5" or "1/3"
+pub fn parse_weight(input: &str) -> Option<f64> {
+    let input = input.trim();
+    match input.split_once('/') {
+        Some((numerator, denominator)) => {
+            Some(numerator.trim().parse::<f64>().ok()? / denominator.trim().parse::<f64>().ok()?)
+        }
+        None => input.parse().ok(),
+    }
+}
+
+// Parses a label like "a", "a,b", "0-9" or "else" into its symbols
+pub fn parse_symbols(label: &str) -> Result<Vec<LabelSymbol>, ParseError> {
+    let mut symbols = Vec::new();
+    for part in label.split(',').map(str::trim) {
+        if let Some(symbol) = named_symbol(part) {
+            symbols.push(symbol);
+            continue;
+        }
+        let chars: Vec<char> = part.chars().collect();
+        match chars[..] {
+            [from, '-', to] if from <= to => symbols.extend((from..=to).map(LabelSymbol::Symbol)),
+            _ => {
+                return Err(ParseError(format!(
+                    "'{}' in the label '{}' is not a symbol, separate symbols with commas",
+                    part, label
+                )))
+            }
+        }
+    }
+    Ok(symbols)
+}
+
+// End markers around the input on the tape of a 2dfa or lba
+pub const LEFT_MARKER: char = '<';
+pub const RIGHT_MARKER: char = '>';
+
+// Where the head of a 2dfa or lba moves after a transition
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Move {
+    Left,
+    Right,
+    Stay,
+}
+
+pub fn parse_move(input: &str) -> Option<Move> {
+    match input.trim().to_uppercase().as_str() {
+        "L" | "<-" => Some(Move::Left),
+        "R" | "->" => Some(Move::Right),
+        "S" | "N" | "-" => Some(Move::Stay),
+        _ => None,
+    }
+}
+
+impl Move {
+    // The new head position, None if the head would leave the tape
+    pub fn apply(self, position: usize, tape_length: usize) -> Option<usize> {
+        match self {
+            Move::Left => position.checked_sub(1),
+            Move::Right => Some(position + 1).filter(|&p| p < tape_length),
+            Move::Stay => Some(position),
+        }
+    }
+}
+
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Move::Left => write!(f, "L"),
+            Move::Right => write!(f, "R"),
+            Move::Stay => write!(f, "S"),
+        }
+    }
+}
+
+// Applies f to every item on up to threads threads (0 uses all cores), the results keep the order of the items
+// Items are dealt out in turns, so neighbouring items of similar cost end up on different threads
+pub fn parallel_map<T: Sync, R: Send>(
+    items: &[T],
+    threads: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let threads = thread_count(threads).min(items.len());
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let f = &f;
+    let mut parts: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|first| {
+                scope.spawn(move || {
+                    items[first..]
+                        .iter()
+                        .step_by(threads)
+                        .map(f)
+                        .collect::<Vec<_>>()
+                        .into_iter()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("a worker thread panicked"))
+            .collect()
+    });
+    (0..items.len())
+        .map(|i| parts[i % threads].next().unwrap())
+        .collect()
+}
+
+// The number of threads to use, 0 stands for all cores
+pub fn thread_count(threads: usize) -> usize {
+    if threads > 0 {
+        threads
     } else {
-        out
+        thread::available_parallelism().map_or(1, NonZeroUsize::get)
     }
 }
 
@@ -26,9 +259,66 @@ pub fn logcheck_w(value: bool, logtext: &str) {
     }
 }
 
-pub fn logcheck_e(value: bool, logtext: &str) {
+pub fn logcheck_e(value: bool, logtext: &str) -> Result<(), ParseError> {
+    // the caller reports the error, logging it here would show it twice
     if value {
-        error!("{}", logtext);
-        panic!("{}", logtext)
+        Err(ParseError::new(logtext))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_parse_symbols() {
+    use LabelSymbol::*;
+    assert_eq!(parse_symbols("a").unwrap(), vec![Symbol('a')]);
+    assert_eq!(
+        parse_symbols("a, b").unwrap(),
+        vec![Symbol('a'), Symbol('b')]
+    );
+    assert_eq!(
+        parse_symbols("0-2,x").unwrap(),
+        vec![Symbol('0'), Symbol('1'), Symbol('2'), Symbol('x')]
+    );
+    assert_eq!(parse_symbols("ε").unwrap(), vec![Epsilon]);
+    assert_eq!(parse_symbols("else").unwrap(), vec![Else]);
+    assert_eq!(parse_symbols("Σ").unwrap(), vec![Any]);
+    assert_eq!(
+        parse_symbols("comma,-").unwrap(),
+        vec![Symbol(','), Symbol('-')]
+    );
+    // a symbol can't be split into two by accident
+    assert!(parse_symbols("ab").is_err());
+    assert!(parse_symbols("a,bc").is_err());
+}
+
+#[test]
+fn test_symbol_label() {
+    use LabelSymbol::*;
+    for symbol in ['a', 'e', 'ε', 'λ', 'Σ', ',', '-', '\''] {
+        assert_eq!(
+            parse_symbols(&symbol_label(symbol)),
+            Ok(vec![Symbol(symbol)])
+        );
+    }
+    assert_eq!(symbol_label('e'), "'e'");
+    assert_eq!(parse_symbols(&symbol_label(' ')), Ok(vec![Epsilon]));
+    assert_eq!(parse_char(&symbol_label('e')), 'e');
+}
+
+#[test]
+fn test_parse_weight() {
+    assert_eq!(parse_weight("0.25"), Some(0.25));
+    assert_eq!(parse_weight(" 1 / 4"), Some(0.25));
+    assert_eq!(parse_weight("a"), None);
+}
+
+#[test]
+fn test_parallel_map() {
+    let items: Vec<usize> = (0..1000).collect();
+    let squares: Vec<usize> = items.iter().map(|i| i * i).collect();
+    for threads in [1, 3, 8] {
+        assert_eq!(parallel_map(&items, threads, |i| i * i), squares);
     }
+    assert!(parallel_map(&[] as &[usize], 4, |i| *i).is_empty());
 }
@@ -0,0 +1,52 @@
+use crate::shared::analysis::{analyze, Analysis};
+use crate::shared::parsing::*;
+
+fn analysis(filepath: &str) -> Option<Analysis> {
+    let a = parse_automaton(filepath, None).expect("testdata missing");
+    analyze(a.as_ref(), a.alphabet(), 4)
+}
+
+#[test]
+fn test_regular() {
+    let two_as = analysis("data/dfa/two-as-dfa.txt").unwrap();
+    assert_eq!(two_as.shortest_word.as_deref(), Some("aa"));
+    let regular = two_as.regular.unwrap();
+    assert!(regular.words.is_none());
+    assert!(!regular.universal);
+    assert_eq!(regular.counts, vec![0, 0, 1, 4, 11]);
+
+    let finite = analysis("data/nfa/finite-nfa.txt").unwrap();
+    assert_eq!(finite.shortest_word.as_deref(), Some("a"));
+    assert_eq!(
+        finite.regular.unwrap().words,
+        Some(vec!["a".to_string(), "ab".to_string(), "ba".to_string()])
+    );
+
+    let universal = analysis("data/dfa/universal-dfa.txt").unwrap();
+    assert_eq!(universal.shortest_word.as_deref(), Some(""));
+    assert!(universal.regular.unwrap().universal);
+}
+
+#[test]
+fn test_pda() {
+    let pda = analysis("data/pda/pda.drawio.xml").unwrap();
+    assert_eq!(pda.shortest_word.as_deref(), Some("b"));
+    assert!(pda.regular.is_none());
+    let a = parse_automaton("data/pda/pda.drawio.xml", None).unwrap();
+    assert!(a.accepts("b") && !a.accepts("a"));
+    let looping = analysis("data/pda/pda-epsilon-loop.txt").unwrap();
+    assert_eq!(looping.shortest_word.as_deref(), Some("a"));
+    assert!(analysis("data/pda/empty-pda.txt")
+        .unwrap()
+        .shortest_word
+        .is_none());
+}
+
+#[test]
+fn test_unsupported() {
+    assert!(analysis("data/buchi/empty-buchi.txt")
+        .unwrap()
+        .shortest_word
+        .is_none());
+    assert!(analysis("data/counter/anbn-counter.txt").is_none());
+}
@@ -0,0 +1,95 @@
+use crate::shared::batch::*;
+use crate::shared::budget::Budget;
+use crate::shared::evaluation::GenerationConfig;
+use crate::shared::parsing::parse_automaton;
+
+#[test]
+fn test_student_id() {
+    assert_eq!(
+        student_id("hand-in/alice.drawio.xml", IdSource::File),
+        "alice"
+    );
+    assert_eq!(
+        student_id("hand-in/alice.smith.drawio.xml", IdSource::File),
+        "alice.smith"
+    );
+    assert_eq!(student_id("hand-in/j.doe.gr", IdSource::File), "j.doe");
+    assert_eq!(
+        student_id("hand-in/bob/dfa.xml", IdSource::Directory),
+        "bob"
+    );
+    assert_eq!(student_id("dfa.xml", IdSource::Directory), "");
+}
+
+#[test]
+fn test_grade_all() {
+    let reference = parse_automaton("data/dfa/universal-dfa.txt", None).unwrap();
+    let evaluation = std::fs::read_to_string("data/dfa/ends-with-a-eval.txt").unwrap();
+    let submissions = [
+        "data/dfa/ends-with-a-dfa.txt".to_string(),
+        "data/dfa/universal-dfa.txt".to_string(),
+        "data/dfa/missing-dfa.txt".to_string(),
+    ];
+    let results = grade_all(
+        &submissions,
+        None,
+        reference.as_ref(),
+        Some(&evaluation),
+        &GenerationConfig::default(),
+        Budget::unlimited,
+        IdSource::File,
+    );
+    let statuses: Vec<_> = results.iter().map(|graded| graded.status).collect();
+    assert_eq!(
+        statuses,
+        vec![Status::Mismatch, Status::Pass, Status::Error]
+    );
+    assert_eq!(results[0].score, Some(0.4));
+    assert!(results[2].error.is_some());
+    let threaded = grade_all(
+        &submissions,
+        None,
+        reference.as_ref(),
+        Some(&evaluation),
+        &GenerationConfig {
+            threads: 3,
+            ..GenerationConfig::default()
+        },
+        Budget::unlimited,
+        IdSource::File,
+    );
+    assert_eq!(threaded, results);
+
+    let csv = to_csv(&results);
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("id,file,score,verdict,error"));
+    assert_eq!(
+        lines.next(),
+        Some("ends-with-a-dfa,data/dfa/ends-with-a-dfa.txt,40,mismatch,")
+    );
+    let columns = MoodleColumns {
+        id: "Email address".to_string(),
+        grade: "Exercise 1".to_string(),
+        max_grade: 10.0,
+    };
+    // the submission that couldn't be read is left out
+    assert_eq!(
+        to_moodle_csv(&results, &columns),
+        "Email address,Exercise 1\nends-with-a-dfa,4\nuniversal-dfa,10\n"
+    );
+}
+
+#[test]
+fn test_quoting() {
+    let results = [Graded {
+        id: "o'neil, anne".to_string(),
+        path: "a.txt".to_string(),
+        score: None,
+        status: Status::Error,
+        error: Some("missing \"s\" line".to_string()),
+    }];
+    assert_eq!(
+        to_csv(&results).lines().nth(1),
+        Some("\"o'neil, anne\",a.txt,,error,\"missing \"\"s\"\" line\"")
+    );
+}
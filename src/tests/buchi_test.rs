@@ -0,0 +1,100 @@
+use crate::automatons::buchi::parse_lasso;
+use crate::shared::automaton::{AutomatonData, AutomatonType};
+use crate::shared::budget::Budget;
+use crate::shared::evaluation::{counterexample, GenerationConfig};
+use crate::shared::parsing::*;
+use crate::tests::test_compare;
+
+use super::{edge, test_against, view_test};
+
+#[test]
+fn parse_text() {
+    parse_automaton("data/buchi/infinitely-many-a-buchi.txt", None).unwrap();
+    parse_automaton("data/buchi/finitely-many-a-buchi.txt", None).unwrap();
+}
+
+#[test]
+fn view() {
+    view_test(&[
+        "data/buchi/infinitely-many-a-buchi.txt",
+        "data/buchi/finitely-many-a-buchi.txt",
+        "data/buchi/empty-buchi.txt",
+    ]);
+}
+
+#[test]
+fn test_lasso() {
+    assert_eq!(parse_lasso("ab(ba)"), Some(("ab", "ba")));
+    assert_eq!(parse_lasso("(a)"), Some(("", "a")));
+    assert_eq!(parse_lasso("ab"), Some(("", "ab")));
+    assert_eq!(parse_lasso("ab()"), None);
+}
+
+#[test]
+fn test_simulation() {
+    test_against(
+        "data/buchi/infinitely-many-a-buchi.txt",
+        &["(a)", "bbb(ab)", "(bba)", "ab"],
+        &["(b)", "aaa(b)", "a(bb)"],
+    );
+    test_against(
+        "data/buchi/finitely-many-a-buchi.txt",
+        &["(b)", "aaa(b)", "a(bb)"],
+        &["(a)", "bbb(ab)", "(bba)"],
+    );
+}
+
+#[test]
+fn test_emptiness() {
+    let check = |path: &str, empty: bool| {
+        let a = parse_automaton(path, None).unwrap();
+        let word = a.shortest_word().expect("büchi automatons find a lasso");
+        assert_eq!(word.is_none(), empty);
+        if let Some(word) = word {
+            assert!(a.accepts(&word));
+        }
+    };
+    check("data/buchi/infinitely-many-a-buchi.txt", false);
+    check("data/buchi/finitely-many-a-buchi.txt", false);
+    check("data/buchi/empty-buchi.txt", true);
+}
+
+#[test]
+fn test_comparison() {
+    let a1 = "data/buchi/infinitely-many-a-buchi.txt";
+    let a2 = "data/buchi/infinitely-many-a-nondet-buchi.txt";
+    let a3 = "data/buchi/finitely-many-a-buchi.txt";
+
+    test_compare(a1, a2, true);
+    test_compare(a1, a3, false);
+}
+
+#[test]
+fn test_epsilon() {
+    let data = vec![
+        AutomatonData::Start(1),
+        AutomatonData::Final(2),
+        edge(1, 2, "ε"),
+        edge(2, 2, "a"),
+    ];
+    assert!(build_automaton(AutomatonType::Buchi, data).is_err());
+}
+
+#[test]
+fn test_empty_alphabet() {
+    let buchi = parse_automaton("data/buchi/empty-buchi.txt", None).unwrap();
+    // there are no infinite words to compare on
+    let config = GenerationConfig {
+        alphabet: Some(Vec::new()),
+        ..GenerationConfig::default()
+    };
+    assert_eq!(
+        counterexample(
+            buchi.as_ref(),
+            buchi.as_ref(),
+            &config,
+            &Budget::unlimited()
+        ),
+        None
+    );
+}
@@ -0,0 +1,59 @@
+use crate::shared::budget::{Budget, Exceeded};
+use crate::shared::parsing::*;
+use crate::tests::test_compare;
+
+use super::{test_against, view_test};
+
+#[test]
+fn parse_text() {
+    parse_automaton("data/counter/anbn-counter.txt", None).unwrap();
+    parse_automaton("data/counter/anbncn-counter.txt", None).unwrap();
+    parse_automaton("data/counter/anbn-empty-counter.txt", None).unwrap();
+}
+
+#[test]
+fn view() {
+    view_test(&[
+        "data/counter/anbn-counter.txt",
+        "data/counter/anbncn-counter.txt",
+    ]);
+}
+
+#[test]
+fn test_simulation() {
+    test_against(
+        "data/counter/anbn-counter.txt",
+        &["", "ab", "aabb", "aaabbb"],
+        &["a", "b", "abb", "aab", "ba", "abab"],
+    );
+    test_against(
+        "data/counter/anbncn-counter.txt",
+        &["", "abc", "aabbcc"],
+        &["ab", "aabcc", "abbc", "abcc", "acb"],
+    );
+}
+
+#[test]
+fn test_comparison() {
+    test_compare(
+        "data/counter/anbn-counter.txt",
+        "data/counter/anbn-empty-counter.txt",
+        true,
+    );
+    test_compare(
+        "data/counter/anbn-counter.txt",
+        "data/counter/anbncn-counter.txt",
+        false,
+    );
+}
+
+#[test]
+fn test_budget() {
+    let a = parse_automaton("data/counter/anbn-counter.txt", None).unwrap();
+    let budget = Budget::new(None, None, Some(3));
+    let mut limit = budget.limit();
+    assert_eq!(
+        a.accepts_within("aaaabbbb", &mut limit),
+        Err(Exceeded::Configurations)
+    );
+}
@@ -1,5 +1,11 @@
+use crate::shared::automaton::AutomatonData;
+use crate::shared::budget::Budget;
+use crate::shared::evaluation::{
+    fixed_comparison, fixed_test, full_comparison, generated_comparison, run_tests, Evaluation,
+    GenerationConfig, Relation, TestCase,
+};
 use crate::shared::parsing::*;
-use crate::tests::test_compare;
+use crate::tests::{test_compare, test_score};
 
 use super::{test_against, view_test};
 
@@ -67,3 +73,182 @@ fn test_comparison() {
     test_compare(a3, a4, false);
     test_compare(a5, a6, false);
 }
+
+#[test]
+fn test_graded_comparison() {
+    let a1 = "data/dfa/is-uneven-dfa.gr";
+    let a2 = "data/dfa/is-uneven-dfa.xml";
+    let a3 = "data/dfa/is-uneven-capped.drawio.xml";
+
+    assert_eq!(test_score(a1, a2), 1.0);
+    let score = test_score(a2, a3);
+    assert!(0.0 < score && score < 1.0);
+}
+
+#[test]
+fn test_shared_alphabet() {
+    let a1 = parse_automaton("data/dfa/is-uneven-dfa.gr", None).unwrap();
+    let a2 = parse_automaton("data/dfa/importantdfa.drawio.xml", None).unwrap();
+    let config = GenerationConfig::default();
+    // letters that only the first automaton uses are tested as well
+    assert_eq!(
+        config.shared_alphabet(a1.as_ref(), a2.as_ref()),
+        vec!['a', 'u', 'w']
+    );
+    let config = GenerationConfig {
+        alphabet: Some(vec!['b', 'a']),
+        ..GenerationConfig::default()
+    };
+    assert_eq!(
+        config.shared_alphabet(a1.as_ref(), a2.as_ref()),
+        vec!['a', 'b']
+    );
+}
+
+#[test]
+fn test_declared_alphabet() {
+    let a = parse_automaton("data/dfa/declared-alphabet-dfa.txt", None).unwrap();
+    assert_eq!(a.alphabet(), &['a', 'b']);
+    test_against(
+        "data/dfa/declared-alphabet-dfa.txt",
+        &["a", "aaa"],
+        &["", "b", "ab"],
+    );
+    let a = parse_automaton("data/dfa/is-uneven-declared-dfa.drawio.xml", None).unwrap();
+    assert_eq!(a.alphabet(), &['a', 'b']);
+    test_compare(
+        "data/dfa/is-uneven-dfa.xml",
+        "data/dfa/is-uneven-declared-dfa.drawio.xml",
+        true,
+    );
+}
+
+#[test]
+fn test_label_symbols() {
+    test_against(
+        "data/dfa/labels-dfa.txt",
+        &["a", "ca", "bba", "abca"],
+        &["", "ac", "ab", "d"],
+    );
+}
+
+#[test]
+fn test_relation() {
+    let relation = |path1: &str, path2: &str, relation: Relation| {
+        let a1 = parse_automaton(path1, None).expect("testdata missing");
+        let a2 = parse_automaton(path2, None).expect("testdata missing");
+        let config = GenerationConfig {
+            relation,
+            random_samples: 1000,
+            ..GenerationConfig::default()
+        };
+        generated_comparison(a1.as_ref(), a2.as_ref(), &config, &Budget::unlimited())
+    };
+    let two_as = "data/dfa/two-as-dfa.txt";
+    let universal = "data/dfa/universal-dfa.txt";
+    assert_eq!(relation(two_as, universal, Relation::Subset), 1);
+    assert_eq!(relation(two_as, universal, Relation::Superset), 0);
+    assert_eq!(relation(universal, two_as, Relation::Superset), 1);
+    assert_eq!(relation(two_as, universal, Relation::Equal), 0);
+    // pdas are compared with the generated words
+    assert_eq!(
+        relation("data/pda/pda.drawio.xml", universal, Relation::Subset),
+        1
+    );
+    assert_eq!(
+        relation("data/pda/pda.drawio.xml", two_as, Relation::Subset),
+        0
+    );
+}
+
+#[test]
+fn test_trace() {
+    let dfa = parse_automaton("data/dfa/ends-with-a-dfa.txt", None).unwrap();
+    assert_eq!(
+        dfa.trace("aba").unwrap(),
+        vec!["start in 1", "1 -a-> 2", "2 -b-> 1", "1 -a-> 2"]
+    );
+}
+
+#[test]
+fn test_expected_verdicts() {
+    let dfa = parse_automaton("data/dfa/ends-with-a-dfa.txt", None).unwrap();
+    let cases = std::fs::read_to_string("data/dfa/ends-with-a-tests.txt").unwrap();
+    assert_eq!(
+        fixed_test(dfa.as_ref(), &cases, &Budget::unlimited()).unwrap(),
+        (6, 6)
+    );
+    // lines without a verdict are only reported
+    let cases = run_tests(dfa.as_ref(), "+ b\nba\n- ε", &Budget::unlimited()).unwrap();
+    assert_eq!(
+        cases,
+        vec![
+            TestCase {
+                word: "b".to_string(),
+                expected: Some("accept".to_string()),
+                actual: "reject".to_string(),
+                passed: false,
+            },
+            TestCase {
+                word: "ba".to_string(),
+                expected: None,
+                actual: "accept".to_string(),
+                passed: true,
+            },
+            TestCase {
+                word: String::new(),
+                expected: Some("reject".to_string()),
+                actual: "reject".to_string(),
+                passed: true,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_evaluation_file() {
+    let dfa = parse_automaton("data/dfa/ends-with-a-dfa.txt", None).unwrap();
+    let universal = parse_automaton("data/dfa/universal-dfa.txt", None).unwrap();
+    let text = std::fs::read_to_string("data/dfa/ends-with-a-eval.txt").unwrap();
+    let evaluation = Evaluation::parse(&text).unwrap();
+    assert_eq!(evaluation.generated_weight, 0.0);
+    assert_eq!(evaluation.groups.len(), 3);
+    assert_eq!(
+        evaluation.groups[0].words,
+        vec![(String::new(), 0.5), ("a".to_string(), 1.0)]
+    );
+    assert_eq!(evaluation.groups[1].name.as_deref(), Some("b words"));
+    assert_eq!(evaluation.groups[1].cap, Some(1.5));
+    // the b words reach 1 of 2 points, capped to 1.5
+    let budget = Budget::unlimited();
+    assert_eq!(
+        fixed_comparison(dfa.as_ref(), universal.as_ref(), &evaluation, &budget),
+        (2.0, 5.0)
+    );
+    let config = GenerationConfig::default();
+    let score = full_comparison(dfa.as_ref(), universal.as_ref(), &text, &config, &budget);
+    assert_eq!(score.unwrap(), 0.4);
+    // the generated comparison counts once by default
+    let score = full_comparison(dfa.as_ref(), dfa.as_ref(), "a 1", &config, &budget);
+    assert_eq!(score.unwrap(), 1.0);
+    assert!(Evaluation::parse("a").is_err());
+    assert!(Evaluation::parse("[group 2").is_err());
+    assert!(Evaluation::parse("@generated x").is_err());
+    for points in ["NaN", "inf", "1/0", "-1"] {
+        assert!(Evaluation::parse(&format!("a {}", points)).is_err());
+        assert!(Evaluation::parse(&format!("[group] {}", points)).is_err());
+        assert!(Evaluation::parse(&format!("@generated {}", points)).is_err());
+    }
+}
+
+#[test]
+fn test_directive_names() {
+    let path = "data/dfa/directive-names-dfa.txt";
+    let data = read_automaton_data(path).unwrap();
+    assert!(!data
+        .iter()
+        .any(|d| matches!(d, AutomatonData::Output(..) | AutomatonData::Threshold(_))));
+    let dfa = parse_automaton(path, None).unwrap();
+    assert!(dfa.accepts("aba"));
+    assert!(!dfa.accepts("ab"));
+}
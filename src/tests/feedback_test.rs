@@ -0,0 +1,62 @@
+use crate::shared::automaton::{AutomatonData, AutomatonType};
+use crate::shared::budget::Budget;
+use crate::shared::evaluation::{run_tests, Witness};
+use crate::shared::feedback::{Report, ReportFormat};
+use crate::shared::parsing::{build_automaton, parse_automaton, read_automaton_data};
+
+use super::edge;
+
+#[test]
+fn test_names() {
+    let data = read_automaton_data("data/dfa/is-uneven-dfa.xml").unwrap();
+    let names: Vec<_> = data
+        .iter()
+        .filter(|d| matches!(d, AutomatonData::Name(..)))
+        .collect();
+    assert_eq!(names.len(), 2);
+    assert!(data.contains(&AutomatonData::Name(1, "s0".to_string())));
+    // in the text format the identifiers are the names, ids are given out in order of appearance
+    let data = read_automaton_data("data/dfa/two-as-dfa.txt").unwrap();
+    assert!(data.contains(&AutomatonData::Name(2, "3".to_string())));
+}
+
+#[test]
+fn test_report() {
+    let path = "data/dfa/is-uneven-dfa.xml";
+    let data = read_automaton_data(path).unwrap();
+    let dfa = parse_automaton(path, None).unwrap();
+    let mut report = Report::new(path, &data, dfa.as_ref());
+    report.tests = Some(run_tests(dfa.as_ref(), "+ a\n+ aa\nb", &Budget::unlimited()).unwrap());
+    report.counterexample = Some(Some(Witness::Differs(String::new())));
+    report.score = Some((2.0, 3.0));
+    let markdown = report.render(ReportFormat::Markdown);
+    assert!(markdown.starts_with("# Feedback for `data/dfa/is-uneven-dfa.xml`\n"));
+    assert!(markdown.contains("- Start state: s0\n- Final states: f1\n"));
+    assert!(markdown.contains("  - s0 --a--> f1\n"));
+    assert!(markdown.contains("1 of 2 tests passed."));
+    assert!(markdown.contains("| `aa` | accept | reject |"));
+    assert!(markdown.contains("differ on the word `ε`"));
+    assert!(markdown.contains("2/3 points"));
+    let text = report.render(ReportFormat::Text);
+    assert!(text.contains("Test words\n----------\n"));
+    assert!(text.contains("- 'aa': expected accept, got reject"));
+    assert!(!text.contains('|'));
+}
+
+#[test]
+fn test_warnings() {
+    let data = vec![
+        AutomatonData::Start(1),
+        AutomatonData::Final(2),
+        edge(1, 2, "a"),
+        edge(3, 2, "a"),
+        AutomatonData::Name(1, "start".to_string()),
+        AutomatonData::Name(2, "end".to_string()),
+        AutomatonData::Name(3, "lost".to_string()),
+    ];
+    let dfa = build_automaton(AutomatonType::DFA, data.clone()).unwrap();
+    let report = Report::new("lost.txt", &data, dfa.as_ref()).render(ReportFormat::Markdown);
+    assert!(report.contains("- state lost can't be reached"));
+    // sections of checks that weren't run are left out
+    assert!(!report.contains("## Score"));
+}
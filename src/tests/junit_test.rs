@@ -0,0 +1,73 @@
+use crate::shared::budget::{Budget, Exceeded};
+use crate::shared::evaluation::{grade_evaluation, run_tests, GenerationConfig, Witness};
+use crate::shared::junit::{to_xml, TestSuite};
+use crate::shared::parsing::parse_automaton;
+
+#[test]
+fn test_words() {
+    let dfa = parse_automaton("data/dfa/ends-with-a-dfa.txt", None).unwrap();
+    let text = std::fs::read_to_string("data/dfa/ends-with-a-tests.txt").unwrap();
+    let tests = run_tests(dfa.as_ref(), &text, &Budget::unlimited()).unwrap();
+    let suite = TestSuite::from_tests(&tests);
+    assert_eq!(suite.cases.len(), 6);
+    assert_eq!(suite.failures(), 0);
+    let tests = run_tests(dfa.as_ref(), "+ b\n- a", &Budget::unlimited()).unwrap();
+    let suite = TestSuite::from_tests(&tests);
+    assert_eq!(
+        suite.cases[0].failure.as_deref(),
+        Some("expected accept, got reject")
+    );
+    assert_eq!(suite.failures(), 2);
+}
+
+#[test]
+fn test_evaluation() {
+    let dfa = parse_automaton("data/dfa/ends-with-a-dfa.txt", None).unwrap();
+    let universal = parse_automaton("data/dfa/universal-dfa.txt", None).unwrap();
+    let text = std::fs::read_to_string("data/dfa/ends-with-a-eval.txt").unwrap();
+    let config = GenerationConfig::default();
+    let budget = Budget::unlimited();
+    let grading = grade_evaluation(dfa.as_ref(), universal.as_ref(), &text, &config, &budget);
+    let suite = TestSuite::from_grading(&grading.unwrap());
+    // ε, b and ab are only accepted by the reference, the generated words don't count
+    assert_eq!(suite.cases.len(), 5);
+    assert_eq!(suite.failures(), 3);
+    assert_eq!(suite.cases[0].name, "ε");
+    assert_eq!(suite.cases[0].class, "evaluation");
+    assert_eq!(suite.cases[2].class, "b words");
+    let grading = grade_evaluation(dfa.as_ref(), universal.as_ref(), "a 1", &config, &budget);
+    let suite = TestSuite::from_grading(&grading.unwrap());
+    assert_eq!(suite.cases[1].name, "generated words");
+    assert!(suite.cases[1].failure.is_some());
+}
+
+#[test]
+fn test_xml() {
+    let suites = [
+        TestSuite::from_counterexample(None),
+        TestSuite::from_counterexample(Some(&Witness::Differs("a\"<b".to_string()))),
+    ];
+    assert_eq!(
+        to_xml(&suites),
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <testsuites tests=\"2\" failures=\"1\">\n\
+         \x20 <testsuite name=\"comparison\" tests=\"1\" failures=\"0\">\n\
+         \x20   <testcase classname=\"comparison\" name=\"generated words\"/>\n\
+         \x20 </testsuite>\n\
+         \x20 <testsuite name=\"comparison\" tests=\"1\" failures=\"1\">\n\
+         \x20   <testcase classname=\"comparison\" name=\"generated words\">\n\
+         \x20     <failure message=\"differs from the reference on &apos;a&quot;&lt;b&apos;\"/>\n\
+         \x20   </testcase>\n\
+         \x20 </testsuite>\n\
+         </testsuites>\n"
+    );
+    // a limit doesn't show that the automatons differ
+    let suite = TestSuite::from_counterexample(Some(&Witness::Exceeded(
+        "ab".to_string(),
+        Exceeded::WordTime,
+    )));
+    assert_eq!(
+        suite.cases[0].failure.as_deref(),
+        Some("time limit per word exceeded on 'ab'")
+    );
+}
@@ -0,0 +1,124 @@
+use crate::automatons::{dfa::DFA, kpda::KPDA, nfa::NFA, pda::PDA};
+use crate::shared::automaton::*;
+use crate::shared::budget::{Budget, Exceeded, Limit};
+use crate::shared::evaluation::{generated_comparison, GenerationConfig};
+use crate::shared::parsing::parse_automaton;
+
+use super::edge;
+
+// words of even length over a and b, as a dfa
+fn even_length() -> Vec<AutomatonData> {
+    vec![
+        AutomatonData::Start(1),
+        AutomatonData::Final(1),
+        edge(1, 2, "a,b"),
+        edge(2, 1, "a,b"),
+    ]
+}
+
+// a custom automaton type, only the required methods are implemented
+struct EvenLength {
+    alphabet: Vec<char>,
+}
+
+impl Automaton for EvenLength {
+    fn accepts_within(&self, word: &str, limit: &mut Limit) -> Result<bool, Exceeded> {
+        limit.step()?;
+        Ok(word.chars().count().is_multiple_of(2))
+    }
+
+    fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    fn view(&self) {}
+
+    fn states(&self) -> Vec<VertexId> {
+        vec![]
+    }
+
+    fn transitions(&self) -> Vec<(VertexId, VertexId, String)> {
+        vec![]
+    }
+}
+
+#[test]
+fn test_structure() {
+    let dfa = DFA::new(even_length()).unwrap();
+    assert_eq!(dfa.states(), vec![1, 2]);
+    let mut transitions = dfa.transitions();
+    transitions.sort();
+    assert_eq!(
+        transitions,
+        vec![
+            (1, 2, "a".to_string()),
+            (1, 2, "b".to_string()),
+            (2, 1, "a".to_string()),
+            (2, 1, "b".to_string()),
+        ]
+    );
+    let nfa = NFA::new(vec![
+        AutomatonData::Start(1),
+        AutomatonData::Final(3),
+        edge(1, 2, "ε"),
+        edge(2, 3, "a"),
+    ])
+    .unwrap();
+    assert_eq!(nfa.states(), vec![1, 2, 3]);
+    assert!(nfa.transitions().contains(&(1, 2, "ε".to_string())));
+    let pda = PDA::new(vec![
+        AutomatonData::Start(1),
+        AutomatonData::Final(2),
+        edge(1, 2, "a,#,#A"),
+    ])
+    .unwrap();
+    assert_eq!(pda.states(), vec![1, 2]);
+    assert_eq!(pda.transitions(), vec![(1, 2, "a,#,#A".to_string())]);
+}
+
+#[test]
+fn test_kpda() {
+    let kpda = KPDA::new(vec![
+        AutomatonData::Start(1),
+        AutomatonData::Final(2),
+        edge(1, 2, "a,a,#,#"),
+    ])
+    .unwrap();
+    assert_eq!(kpda.states(), vec![1, 2]);
+    assert_eq!(kpda.transitions(), vec![(1, 2, "a,a,#,#".to_string())]);
+    assert!(kpda.accepts("a"));
+    assert!(!kpda.accepts("b"));
+}
+
+#[test]
+fn test_custom_type() {
+    let custom = EvenLength {
+        alphabet: vec!['a', 'b'],
+    };
+    let dfa = DFA::new(even_length()).unwrap();
+    assert!(custom.accepts("ab"));
+    assert!(!custom.is_transducer());
+    assert_eq!(
+        generated_comparison(
+            &custom,
+            &dfa,
+            &GenerationConfig::default(),
+            &Budget::unlimited()
+        ),
+        1
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    // malformed input is reported instead of panicking
+    assert!(DFA::new(vec![AutomatonData::Final(1), edge(1, 1, "a")]).is_err());
+    assert!(PDA::new(vec![
+        AutomatonData::Start(1),
+        AutomatonData::Final(2),
+        edge(1, 2, "a"),
+    ])
+    .is_err());
+    assert!(parse_automaton("data/dfa/missing-dfa.txt", None).is_err());
+    assert!(determine_automaton_type("tm").is_err());
+}
@@ -0,0 +1,56 @@
+use crate::shared::automaton::AutomatonData;
+use crate::shared::lint::{lint, Lint};
+use crate::shared::parsing::read_automaton_data;
+
+use super::edge;
+
+#[test]
+fn test_lint() {
+    let data = read_automaton_data("data/pda/empty-pda.txt").expect("testdata missing");
+    assert_eq!(
+        lint(&data),
+        vec![Lint::Unreachable(2), Lint::Dead(1), Lint::Dead(3)]
+    );
+    let data = read_automaton_data("data/dfa/ends-with-a-dfa.txt").expect("testdata missing");
+    assert_eq!(lint(&data), vec![]);
+}
+
+#[test]
+fn test_text_box() {
+    // the title and the alphabet declaration aren't states
+    let data = read_automaton_data("data/dfa/titled-dfa.drawio.xml").expect("testdata missing");
+    assert_eq!(lint(&data), vec![]);
+    assert!(!data
+        .iter()
+        .any(|d| matches!(d, AutomatonData::Name(_, name) if name.starts_with("Exercise"))));
+}
+
+#[test]
+fn test_structure() {
+    let data = vec![
+        AutomatonData::Start(1),
+        AutomatonData::Final(2),
+        edge(1, 2, "a"),
+        edge(1, 2, "a"),
+        edge(3, 2, "b"),
+    ];
+    assert_eq!(
+        lint(&data),
+        vec![Lint::Duplicate(1, 2, "a".to_string()), Lint::Unreachable(3)]
+    );
+    // the trap state of a complete dfa is no mistake
+    let starts_with_a = vec![
+        AutomatonData::Start(1),
+        AutomatonData::Final(2),
+        edge(1, 2, "a"),
+        edge(1, 3, "b"),
+        edge(2, 2, "a,b"),
+        edge(3, 3, "a,b"),
+    ];
+    assert_eq!(lint(&starts_with_a), vec![]);
+    // without a start state, reachability can't be checked
+    assert_eq!(
+        lint(&data[1..]),
+        vec![Lint::NoStartState, Lint::Duplicate(1, 2, "a".to_string())]
+    );
+}
@@ -1,15 +1,59 @@
 #[cfg(test)]
-use crate::shared::{evaluation::generated_comparison, parsing::parse_automaton};
+use crate::shared::{
+    automaton::{AutomatonData, VertexId},
+    budget::Budget,
+    evaluation::{generated_comparison, graded_comparison, GenerationConfig},
+    parsing::parse_automaton,
+};
+
+#[cfg(test)]
+pub mod analysis_test;
+
+#[cfg(test)]
+pub mod batch_test;
+
+#[cfg(test)]
+pub mod buchi_test;
+
+#[cfg(test)]
+pub mod counter_test;
 
 #[cfg(test)]
 pub mod dfa_test;
 
+#[cfg(test)]
+pub mod feedback_test;
+
+#[cfg(test)]
+pub mod junit_test;
+
+#[cfg(test)]
+pub mod library_test;
+
+#[cfg(test)]
+pub mod lint_test;
+
 #[cfg(test)]
 pub mod nfa_test;
 
+#[cfg(test)]
+pub mod operations_test;
+
 #[cfg(test)]
 pub mod pda_test;
 
+#[cfg(test)]
+pub mod rubric_test;
+
+#[cfg(test)]
+pub mod tape_test;
+
+#[cfg(test)]
+pub mod transducer_test;
+
+#[cfg(test)]
+pub mod wfa_test;
+
 #[cfg(test)]
 fn test_against(filepath: &str, accept: &[&str], reject: &[&str]) {
     let a = parse_automaton(filepath, None).expect("testdata missing");
@@ -23,7 +67,31 @@ fn test_against(filepath: &str, accept: &[&str], reject: &[&str]) {
 fn test_compare(filepath: &str, filepath2: &str, equivalent: bool) {
     let a1 = parse_automaton(filepath, None).expect("testdata missing");
     let a2 = parse_automaton(filepath2, None).expect("testdata missing");
-    assert!(generated_comparison(&a1, &a2) == if equivalent { 1 } else { 0 },);
+    assert!(
+        generated_comparison(
+            a1.as_ref(),
+            a2.as_ref(),
+            &GenerationConfig::default(),
+            &Budget::unlimited()
+        ) == if equivalent { 1 } else { 0 },
+    );
+}
+
+#[cfg(test)]
+fn test_score(filepath: &str, filepath2: &str) -> f64 {
+    let a1 = parse_automaton(filepath, None).expect("testdata missing");
+    let a2 = parse_automaton(filepath2, None).expect("testdata missing");
+    graded_comparison(
+        a1.as_ref(),
+        a2.as_ref(),
+        &GenerationConfig::default(),
+        &Budget::unlimited(),
+    )
+}
+
+#[cfg(test)]
+fn edge(source: VertexId, target: VertexId, label: &str) -> AutomatonData {
+    AutomatonData::Edge(source, target, label.to_string())
 }
 
 #[cfg(test)]
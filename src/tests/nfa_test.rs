@@ -1,3 +1,5 @@
+use crate::shared::budget::Budget;
+use crate::shared::evaluation::{counterexample, GenerationConfig};
 use crate::shared::parsing::*;
 use crate::tests::test_compare;
 
@@ -42,3 +44,43 @@ fn test_comparison() {
     test_compare(a1, a2, true);
     test_compare(a2, a3, true);
 }
+
+#[test]
+fn test_label_symbols() {
+    test_against(
+        "data/nfa/labels-nfa.txt",
+        &["1+", "+-0-", "99+12", "5-"],
+        &["", "+1", "12", "-+"],
+    );
+    test_compare(
+        "data/nfa/labels-nfa.txt",
+        "data/nfa/labels-explicit-nfa.txt",
+        true,
+    );
+}
+
+#[test]
+fn test_trace() {
+    let nfa = parse_automaton("data/nfa/finite-nfa.txt", None).unwrap();
+    let steps = nfa.trace("bb").unwrap();
+    assert_eq!(steps.len(), 3);
+    assert_eq!(steps[0], "start in {1}");
+    assert!(steps[2].ends_with("-b-> ∅"));
+}
+
+#[test]
+fn test_subset_limit() {
+    let nfa = parse_automaton("data/nfa/eighteenth-from-end-nfa.txt", None).unwrap();
+    assert!(nfa.accepts("abbbbbbbbbbbbbbbbb"));
+    assert!(!nfa.accepts("babbbbbbbbbbbbbbbb"));
+    // too many subsets, so it is compared on generated words instead
+    assert!(nfa.to_regular(&['a', 'b']).is_none());
+    let config = GenerationConfig {
+        random_samples: 100,
+        ..GenerationConfig::default()
+    };
+    assert_eq!(
+        counterexample(nfa.as_ref(), nfa.as_ref(), &config, &Budget::unlimited()),
+        None
+    );
+}
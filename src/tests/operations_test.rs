@@ -0,0 +1,120 @@
+use crate::shared::automaton::Automaton;
+use crate::shared::budget::Budget;
+use crate::shared::evaluation::{generated_comparison, GenerationConfig};
+use crate::shared::operations::Operation;
+use crate::shared::parsing::*;
+
+const TWO_AS: &str = "data/dfa/two-as-dfa.txt";
+const ENDS_WITH_A: &str = "data/dfa/ends-with-a-dfa.txt";
+
+fn build(operation: Operation, paths: &[&str]) -> Box<dyn Automaton> {
+    let automatons: Vec<Box<dyn Automaton>> = paths
+        .iter()
+        .map(|path| parse_automaton(path, None).expect("testdata missing"))
+        .collect();
+    let data = operation
+        .apply(
+            automatons[0].as_ref(),
+            automatons.get(1).map(|a| a.as_ref()),
+            &['a', 'b'],
+        )
+        .expect("finite automatons");
+    build_automaton(operation.result_type(), data).unwrap()
+}
+
+// both automatons are small, so short words are enough
+fn short_words() -> GenerationConfig {
+    GenerationConfig {
+        random_samples: 1000,
+        random_lengths: 0..12,
+        ..GenerationConfig::default()
+    }
+}
+
+fn check(automaton: &dyn Automaton, accept: &[&str], reject: &[&str]) {
+    accept
+        .iter()
+        .for_each(|word| assert!(automaton.accepts(word), "{}", word));
+    reject
+        .iter()
+        .for_each(|word| assert!(!automaton.accepts(word), "{}", word));
+}
+
+#[test]
+fn test_product() {
+    let union = build(Operation::Union, &[TWO_AS, ENDS_WITH_A]);
+    check(union.as_ref(), &["a", "aab", "ba", "aa"], &["", "b", "ab"]);
+    let intersection = build(Operation::Intersection, &[TWO_AS, ENDS_WITH_A]);
+    check(
+        intersection.as_ref(),
+        &["aa", "aba", "baba"],
+        &["a", "aab", "ba", ""],
+    );
+}
+
+#[test]
+fn test_complement() {
+    let complement = build(Operation::Complement, &[ENDS_WITH_A]);
+    check(complement.as_ref(), &["", "b", "ab"], &["a", "ba"]);
+}
+
+#[test]
+fn test_minimize() {
+    let minimal = build(Operation::Minimize, &["data/dfa/universal-dfa.txt"]);
+    assert_eq!(minimal.states().len(), 1);
+    check(minimal.as_ref(), &["", "a", "ab"], &[]);
+}
+
+#[test]
+fn test_epsilon() {
+    let concatenation = build(Operation::Concatenation, &[ENDS_WITH_A, TWO_AS]);
+    check(
+        concatenation.as_ref(),
+        &["aaa", "abaa", "aaba"],
+        &["", "aa", "aab", "baa"],
+    );
+    let star = build(Operation::Star, &[ENDS_WITH_A]);
+    check(star.as_ref(), &["", "a", "ba", "bbaba"], &["b", "ab"]);
+    // the subset construction follows the epsilon transitions as well
+    let star_again = build_automaton(
+        Operation::Star.result_type(),
+        Operation::Star
+            .apply(star.as_ref(), None, &['a', 'b'])
+            .expect("finite automaton"),
+    )
+    .unwrap();
+    assert_eq!(
+        generated_comparison(
+            star.as_ref(),
+            star_again.as_ref(),
+            &short_words(),
+            &Budget::unlimited()
+        ),
+        1
+    );
+}
+
+#[test]
+fn test_text_export() {
+    let data = Operation::Concatenation
+        .apply(
+            parse_automaton(ENDS_WITH_A, None).unwrap().as_ref(),
+            parse_automaton(TWO_AS, None).ok().as_deref(),
+            &['a', 'b'],
+        )
+        .unwrap();
+    let path = std::env::temp_dir().join("automatonator-concatenation-nfa.txt");
+    std::fs::write(&path, to_text(&data)).unwrap();
+    let parsed = parse_automaton(path.to_str().unwrap(), None).unwrap();
+    let built = build_automaton(Operation::Concatenation.result_type(), data).unwrap();
+    assert_eq!(parsed.alphabet(), &['a', 'b']);
+    assert_eq!(
+        generated_comparison(
+            parsed.as_ref(),
+            built.as_ref(),
+            &short_words(),
+            &Budget::unlimited()
+        ),
+        1
+    );
+}
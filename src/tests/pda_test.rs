@@ -1,3 +1,7 @@
+use std::time::Duration;
+
+use crate::shared::budget::{Budget, Exceeded};
+use crate::shared::evaluation::{counterexample, graded_comparison, GenerationConfig};
 use crate::shared::parsing::*;
 use crate::tests::test_compare;
 
@@ -35,3 +39,69 @@ fn test_comparison() {
 
     test_compare(a1, a2, false);
 }
+
+#[test]
+fn test_budget() {
+    let a = parse_automaton("data/pda/pda-epsilon-loop.txt", None).expect("testdata missing");
+    let budget = Budget::new(None, None, Some(10_000));
+    assert!(!budget.was_exceeded());
+    assert_eq!(
+        a.accepts_within("a", &mut budget.limit()),
+        Err(Exceeded::Configurations)
+    );
+    assert!(budget.was_exceeded());
+    let budget = Budget::new(Some(Duration::from_millis(10)), None, None);
+    assert_eq!(
+        a.accepts_within("a", &mut budget.limit()),
+        Err(Exceeded::WordTime)
+    );
+}
+
+#[test]
+fn test_threads() {
+    let a1 = parse_automaton("data/pda/pda.drawio.xml", None).unwrap();
+    let a2 = parse_automaton("data/pda/pdacompli.drawio.xml", None).unwrap();
+    let results: Vec<_> = [1, 2, 5]
+        .into_iter()
+        .map(|threads| {
+            // more random words than fit into one batch, with the sampler learning from earlier ones
+            let config = GenerationConfig {
+                exhaustive_max_length: 4,
+                random_samples: 2500,
+                random_lengths: 0..12,
+                boundary_bias: 0.5,
+                threads,
+                ..GenerationConfig::default()
+            };
+            let budget = Budget::unlimited();
+            (
+                counterexample(a1.as_ref(), a2.as_ref(), &config, &budget),
+                graded_comparison(a1.as_ref(), a2.as_ref(), &config, &budget),
+            )
+        })
+        .collect();
+    assert!(results[0].0.is_some());
+    assert!(results.iter().all(|result| *result == results[0]));
+}
+
+#[test]
+fn test_score_lengths() {
+    let a1 = parse_automaton("data/pda/pda.drawio.xml", None).unwrap();
+    let a2 = parse_automaton("data/pda/pdacompli.drawio.xml", None).unwrap();
+    let config = GenerationConfig {
+        exhaustive_max_length: 4,
+        random_samples: 500,
+        ..GenerationConfig::default()
+    };
+    let budget = Budget::unlimited();
+    assert!(graded_comparison(a1.as_ref(), a2.as_ref(), &config, &budget) < 1.0);
+    // both reject the empty word, longer words don't count
+    let config = GenerationConfig {
+        score_max_length: 0,
+        ..config
+    };
+    assert_eq!(
+        graded_comparison(a1.as_ref(), a2.as_ref(), &config, &budget),
+        1.0
+    );
+}
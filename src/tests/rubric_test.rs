@@ -0,0 +1,154 @@
+use crate::shared::automaton::{AutomatonData, AutomatonType};
+use crate::shared::budget::Budget;
+use crate::shared::evaluation::GenerationConfig;
+use crate::shared::parsing::{build_automaton, parse_automaton, read_automaton_data};
+use crate::shared::rubric::{grade, Criterion, Rubric};
+
+use super::edge;
+
+const ENDS_WITH_A: &str = "data/dfa/ends-with-a-dfa.txt";
+
+fn rubric() -> Rubric {
+    let text = std::fs::read_to_string("data/dfa/ends-with-a-rubric.txt").unwrap();
+    Rubric::parse(&text).unwrap()
+}
+
+#[test]
+fn test_parse() {
+    let rubric = rubric();
+    assert_eq!(rubric.criteria.len(), 6);
+    assert_eq!(rubric.criteria[2], (Criterion::States(0), 1.0));
+    assert_eq!(
+        rubric.criteria[5],
+        (
+            Criterion::Rejects(vec![String::new(), "b".to_string(), "ab".to_string()]),
+            0.5
+        )
+    );
+    assert!(Rubric::parse("equivalent").is_err());
+    assert!(Rubric::parse("states 1 x").is_err());
+    assert!(Rubric::parse("accept 1").is_err());
+    assert!(Rubric::parse("colorful 1").is_err());
+}
+
+#[test]
+fn test_grade() {
+    let data = read_automaton_data(ENDS_WITH_A).unwrap();
+    let dfa = parse_automaton(ENDS_WITH_A, None).unwrap();
+    let config = GenerationConfig::default();
+    let budget = Budget::unlimited();
+    let scores = grade(
+        &rubric(),
+        &data,
+        &AutomatonType::DFA,
+        dfa.as_ref(),
+        dfa.as_ref(),
+        &config,
+        &budget,
+    );
+    assert!(scores.iter().all(|score| score.points == score.max));
+    // against the automaton of all words
+    let universal = parse_automaton("data/dfa/universal-dfa.txt", None).unwrap();
+    let scores = grade(
+        &rubric(),
+        &data,
+        &AutomatonType::DFA,
+        dfa.as_ref(),
+        universal.as_ref(),
+        &config,
+        &budget,
+    );
+    let deductions: Vec<_> = scores
+        .iter()
+        .map(|score| score.deduction.as_deref())
+        .collect();
+    assert_eq!(
+        deductions,
+        vec![
+            Some("the automatons differ on 'ε'"),
+            None,
+            Some("2 states, at most 1 are needed"),
+            None,
+            None,
+            None,
+        ]
+    );
+    assert_eq!(scores[0].points, 0.0);
+}
+
+#[test]
+fn test_trap_state() {
+    // starts with a, complete with a trap state
+    let data = vec![
+        AutomatonData::Start(1),
+        AutomatonData::Final(2),
+        edge(1, 2, "a"),
+        edge(1, 3, "b"),
+        edge(2, 2, "a,b"),
+        edge(3, 3, "a,b"),
+    ];
+    let complete = build_automaton(AutomatonType::DFA, data.clone()).unwrap();
+    // the same language without the trap state
+    let partial = build_automaton(AutomatonType::DFA, data[..4].to_vec()).unwrap();
+    let rubric = Rubric::parse("states 1 0").unwrap();
+    let config = GenerationConfig::default();
+    let budget = Budget::unlimited();
+    for reference in [&complete, &partial] {
+        let scores = grade(
+            &rubric,
+            &data,
+            &AutomatonType::DFA,
+            complete.as_ref(),
+            reference.as_ref(),
+            &config,
+            &budget,
+        );
+        assert_eq!(scores[0].deduction, None);
+    }
+}
+
+#[test]
+fn test_structure() {
+    let data = vec![
+        AutomatonData::Start(1),
+        AutomatonData::Final(2),
+        edge(1, 2, "a"),
+        edge(1, 1, "a,b"),
+        edge(3, 2, "b"),
+    ];
+    let rubric = Rubric::parse("deterministic 1\nreachable 1").unwrap();
+    let nfa = parse_automaton("data/nfa/finite-nfa.txt", None).unwrap();
+    let scores = grade(
+        &rubric,
+        &data,
+        &AutomatonType::NFA,
+        nfa.as_ref(),
+        nfa.as_ref(),
+        &GenerationConfig::default(),
+        &Budget::unlimited(),
+    );
+    assert_eq!(
+        scores[0].deduction.as_deref(),
+        Some("state 1 has more than one transition for 'a'")
+    );
+    assert_eq!(scores[1].deduction.as_deref(), Some("can't reach 3"));
+}
+
+#[test]
+fn test_deterministic_pda() {
+    // the labels of a pda aren't just symbols, so determinism isn't checked
+    let path = "data/pda/empty-pda.txt";
+    let data = read_automaton_data(path).unwrap();
+    let pda = parse_automaton(path, None).unwrap();
+    let rubric = Rubric::parse("deterministic 1").unwrap();
+    let scores = grade(
+        &rubric,
+        &data,
+        &AutomatonType::PDA,
+        pda.as_ref(),
+        pda.as_ref(),
+        &GenerationConfig::default(),
+        &Budget::unlimited(),
+    );
+    assert_eq!(scores[0].points, 0.0);
+}
@@ -0,0 +1,72 @@
+use crate::shared::parsing::*;
+use crate::shared::utils::{parse_move, Move};
+use crate::tests::test_compare;
+
+use super::{test_against, test_score, view_test};
+
+#[test]
+fn parse_text() {
+    parse_automaton("data/2dfa/ends-with-a-2dfa.txt", None).unwrap();
+    parse_automaton("data/2dfa/loop-2dfa.txt", None).unwrap();
+    parse_automaton("data/lba/anbncn-lba.txt", None).unwrap();
+}
+
+#[test]
+fn view() {
+    view_test(&[
+        "data/2dfa/ends-with-a-2dfa.txt",
+        "data/2dfa/loop-2dfa.txt",
+        "data/lba/anbncn-lba.txt",
+    ]);
+}
+
+#[test]
+fn test_moves() {
+    assert_eq!(parse_move("L"), Some(Move::Left));
+    assert_eq!(parse_move(" r"), Some(Move::Right));
+    assert_eq!(parse_move("S"), Some(Move::Stay));
+    assert_eq!(parse_move("a"), None);
+    assert_eq!(Move::Left.apply(0, 3), None);
+    assert_eq!(Move::Right.apply(2, 3), None);
+    assert_eq!(Move::Right.apply(1, 3), Some(2));
+}
+
+#[test]
+fn test_two_way() {
+    test_against(
+        "data/2dfa/ends-with-a-2dfa.txt",
+        &["a", "ba", "abba"],
+        &["", "b", "ab", "aab"],
+    );
+    // loops are detected and rejected
+    test_against("data/2dfa/loop-2dfa.txt", &[], &["", "a", "aaa"]);
+}
+
+#[test]
+fn test_two_way_comparison() {
+    test_compare(
+        "data/2dfa/ends-with-a-2dfa.txt",
+        "data/dfa/ends-with-a-dfa.txt",
+        true,
+    );
+    // compared exactly through the conversion into a dfa
+    assert_eq!(
+        test_score(
+            "data/2dfa/ends-with-a-2dfa.txt",
+            "data/dfa/ends-with-a-dfa.txt"
+        ),
+        1.0
+    );
+    assert!(test_score("data/2dfa/loop-2dfa.txt", "data/dfa/ends-with-a-dfa.txt") < 1.0);
+}
+
+#[test]
+fn test_linear_bounded() {
+    test_against(
+        "data/lba/anbncn-lba.txt",
+        &["", "abc", "aabbcc", "aaabbbccc"],
+        &["a", "abcc", "aabbc", "abcabc", "acb", "bca"],
+    );
+    let lba = parse_automaton("data/lba/anbncn-lba.txt", None).unwrap();
+    assert_eq!(lba.alphabet(), &['a', 'b', 'c']);
+}
@@ -0,0 +1,82 @@
+use crate::shared::automaton::AutomatonData;
+use crate::shared::budget::Budget;
+use crate::shared::evaluation::fixed_test;
+use crate::shared::parsing::*;
+use crate::tests::test_compare;
+
+use super::view_test;
+
+#[test]
+fn parse() {
+    parse_automaton("data/mealy/parity-mealy.txt", None).unwrap();
+    parse_automaton("data/mealy/invert-mealy.txt", None).unwrap();
+    parse_automaton("data/moore/parity-moore.drawio.xml", None).unwrap();
+}
+
+#[test]
+fn view() {
+    view_test(&[
+        "data/mealy/parity-mealy.txt",
+        "data/mealy/invert-mealy.txt",
+        "data/moore/parity-moore.drawio.xml",
+    ]);
+}
+
+#[test]
+fn test_translation() {
+    let mealy = parse_automaton("data/mealy/invert-mealy.txt", None).unwrap();
+    assert_eq!(mealy.translate("0110").as_deref(), Some("1001"));
+    assert_eq!(mealy.translate("012"), None);
+    let moore = parse_automaton("data/moore/parity-moore.drawio.xml", None).unwrap();
+    assert_eq!(moore.translate("").as_deref(), Some(""));
+    assert_eq!(moore.translate("abba").as_deref(), Some("1110"));
+}
+
+#[test]
+fn test_translation_cases() {
+    let cases = std::fs::read_to_string("data/mealy/parity-tests.txt").unwrap();
+    let mealy = parse_automaton("data/mealy/parity-mealy.txt", None).unwrap();
+    assert_eq!(
+        fixed_test(mealy.as_ref(), &cases, &Budget::unlimited()).unwrap(),
+        (4, 4)
+    );
+    let invert = parse_automaton("data/mealy/invert-mealy.txt", None).unwrap();
+    assert_eq!(
+        fixed_test(invert.as_ref(), &cases, &Budget::unlimited()).unwrap(),
+        (1, 4)
+    );
+}
+
+#[test]
+fn test_comparison() {
+    test_compare(
+        "data/mealy/parity-mealy.txt",
+        "data/moore/parity-moore.drawio.xml",
+        true,
+    );
+    test_compare(
+        "data/mealy/parity-mealy.txt",
+        "data/mealy/invert-mealy.txt",
+        false,
+    );
+}
+
+#[test]
+fn test_xml_outputs() {
+    let data = read_automaton_data("data/moore/parity-moore.drawio.xml").unwrap();
+    let outputs: Vec<_> = data
+        .iter()
+        .filter_map(|d| match d {
+            AutomatonData::Output(_, output) => Some(output.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(outputs.len(), 3);
+    assert!(outputs.contains(&"1"));
+    assert!(data
+        .iter()
+        .any(|d| matches!(d, AutomatonData::Name(_, name) if name == "q0")));
+    // states without a slash have no output
+    let data = read_automaton_data("data/dfa/is-uneven-dfa.xml").unwrap();
+    assert!(!data.iter().any(|d| matches!(d, AutomatonData::Output(..))));
+}
@@ -0,0 +1,50 @@
+use crate::shared::budget::Budget;
+use crate::shared::evaluation::fixed_test;
+use crate::shared::parsing::*;
+use crate::tests::test_compare;
+
+use super::{test_against, view_test};
+
+#[test]
+fn parse_text() {
+    parse_automaton("data/wfa/coin-wfa.txt", None).unwrap();
+}
+
+#[test]
+fn view() {
+    view_test(&["data/wfa/coin-wfa.txt"]);
+}
+
+#[test]
+fn test_weight() {
+    let wfa = parse_automaton("data/wfa/coin-wfa.txt", None).unwrap();
+    assert_eq!(wfa.weight(""), Some(0.0));
+    assert_eq!(wfa.weight("a"), Some(0.5));
+    assert_eq!(wfa.weight("aba"), Some(0.75));
+    let dfa = parse_automaton("data/dfa/two-as-dfa.txt", None).unwrap();
+    assert_eq!(dfa.weight("aa"), None);
+}
+
+#[test]
+fn test_threshold() {
+    test_against(
+        "data/wfa/coin-wfa.txt",
+        &["aa", "abab", "aaa"],
+        &["", "a", "bab"],
+    );
+}
+
+#[test]
+fn test_weight_cases() {
+    let cases = std::fs::read_to_string("data/wfa/coin-tests.txt").unwrap();
+    let wfa = parse_automaton("data/wfa/coin-wfa.txt", None).unwrap();
+    assert_eq!(
+        fixed_test(wfa.as_ref(), &cases, &Budget::unlimited()).unwrap(),
+        (5, 6)
+    );
+}
+
+#[test]
+fn test_comparison() {
+    test_compare("data/wfa/coin-wfa.txt", "data/dfa/two-as-dfa.txt", true);
+}